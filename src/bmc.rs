@@ -2,16 +2,18 @@ use crate::{
     BlCex, BlEngine, Engine, McResult,
     config::{EngineConfig, EngineConfigBase, PreprocConfig},
     impl_config_deref,
+    report::{Report, TsStatistic},
     tracer::{ExtractorIf, Tracer, TracerIf},
     transys::{Transys, TransysIf, certify::Restore, nodep::NoDepTransys, unroll::TransysUnroll},
     utils::EngineCtrl,
 };
 use clap::{Args, Parser};
 use giputils::TerminateCtrl;
-use log::info;
+use log::{error, info};
 use logicrs::{LitVec, satif::Satif};
 use rand::{RngExt, SeedableRng, rngs::StdRng};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::{
     ops::Deref,
     sync::{Arc, Mutex},
@@ -62,6 +64,9 @@ pub struct BMC {
     tracer: Tracer,
     extractor: Option<Box<dyn ExtractorIf>>,
     invariant: Vec<LitVec>,
+    /// number and total time of the solver calls
+    num_solve: usize,
+    solve_time: Duration,
     ctrl: Arc<BmcCtrl>,
 }
 
@@ -84,7 +89,11 @@ impl TerminateCtrl for BmcCtrl {
 impl BMC {
     pub fn new(cfg: BMCConfig, mut ts: Transys) -> Self {
         let ots = ts.clone();
-        ts.compress_bads();
+        if let Some(prop) = cfg.prop {
+            ts.bad = LitVec::from(ts.bad[prop]);
+        } else {
+            ts.compress_bads();
+        }
         let mut rng = StdRng::seed_from_u64(cfg.rseed);
         let rst = Restore::new(&ts);
         let (ts, mut rst) = ts.preproc(&cfg.preproc, rst);
@@ -121,6 +130,8 @@ impl BMC {
             tracer: Tracer::new(),
             extractor: None,
             invariant: Vec::new(),
+            num_solve: 0,
+            solve_time: Duration::ZERO,
             ctrl: Arc::new(BmcCtrl {
                 base: EngineCtrl::new(),
                 solver: Mutex::new(solver_ctrl),
@@ -197,12 +208,15 @@ impl Engine for BMC {
                 }
                 assump.clear();
             }
+            let solve_start = Instant::now();
             let r = if let Some(limit) = time_limit {
                 self.solver
                     .solve_with_limit(&assump, vec![], Duration::from_secs(limit))
             } else {
                 self.solver.try_solve(&assump, vec![])
             };
+            self.num_solve += 1;
+            self.solve_time += solve_start.elapsed();
             let Some(r) = r else {
                 if self.ctrl.is_terminated() {
                     return McResult::Unknown(k.checked_sub(1));
@@ -227,6 +241,17 @@ impl Engine for BMC {
         self.tracer.add_tracer(tracer);
     }
 
+    fn report(&mut self, report: &mut Report) {
+        report.preproc_ts = Some(TsStatistic::new(self._ts.deref()));
+        report.statistic.insert(
+            "solver".to_string(),
+            json!({
+                "num_solve": self.num_solve,
+                "solve_time": self.solve_time.as_secs_f64(),
+            }),
+        );
+    }
+
     fn set_extractor(&mut self, extractor: Box<dyn ExtractorIf>) {
         self.extractor = Some(extractor);
     }
@@ -239,11 +264,13 @@ impl Engine for BMC {
 impl BlEngine for BMC {
     fn cex(&mut self) -> BlCex {
         let mut cex = self.uts.cex(self.solver.as_ref());
-        cex = cex.map(|l| self.rst.restore(l));
-        for s in cex.state.iter_mut() {
-            *s = self.rst.restore_eq_state(s);
+        cex = self.rst.restore_cex(&cex);
+        if let Some(prop) = self.cfg.prop {
+            cex.bad_id = prop;
+        }
+        if let Err(err) = cex.exact_state(&self.ots, true, self.rst.enlarge()) {
+            error!("failed to complete the cex: {err}");
         }
-        cex.exact_state(&self.ots, true);
         cex
     }
 }
@@ -6,6 +6,7 @@ use crate::{
     config::EngineConfigBase,
     ic3::{IC3, IC3Config},
     impl_config_deref,
+    report::Report,
     tracer::{Tracer, TracerIf},
     wltransys::{WlTransys, bitblast::BitblastMap},
 };
@@ -84,6 +85,10 @@ impl Engine for Cegar {
         self.model.ic3.statistic();
     }
 
+    fn report(&mut self, report: &mut Report) {
+        self.model.ic3.report(report);
+    }
+
     fn get_ctrl(&self) -> Arc<dyn TerminateCtrl> {
         self.model.ic3.get_ctrl()
     }
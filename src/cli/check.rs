@@ -1,19 +1,25 @@
-use crate::logger_init;
+use crate::{
+    cli::vacuity::{self, VacuityConfig},
+    logger_init,
+};
 use anyhow::{Context, bail};
 use clap::{ArgAction, Parser};
-use log::info;
+use log::{info, warn};
+use logicrs::{LitVec, VarSymbols};
 use rIC3::{
-    BlEngine, Engine, McResult,
-    config::EngineConfig,
-    create_bl_engine, create_wl_engine,
-    frontend::{certificate_check, frontend_from_model},
-    portfolio::{Portfolio, PortfolioConfig},
+    BlEngine, Engine, McBlCertificate, McResult, MpEngine, MpMcResult,
+    config::{EngineConfig, WorkerConfigs},
+    create_bl_engine, create_mp_engine, create_wl_engine,
+    frontend::{Frontend, certificate_check, frontend_from_model},
+    polynexus::PolyNexusConfig,
+    portfolio::{self, Portfolio, PortfolioConfig},
+    report::{PropReport, Report, TsStatistic},
     tracer::LogTracer,
-    transys::TransysIf,
+    transys::{Transys, TransysIf, certify::BlProof},
     ui::UiRenderer,
     utils::install_interrupt_handler,
 };
-use std::{env, fs, path::PathBuf, process::exit};
+use std::{env, fs, mem::take, path::PathBuf, process::exit};
 
 #[derive(Parser, Debug, Clone)]
 pub struct CheckConfig {
@@ -41,6 +47,24 @@ pub struct CheckConfig {
     /// interrupt statistic
     #[arg(long, default_value_t = false)]
     pub interrupt_statistic: bool,
+
+    /// write a JSON report of the run
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// check each property of a model with several properties on its own,
+    /// with polynexus workers running the given engine, and print the result
+    /// of each property
+    #[arg(long, default_value_t = false)]
+    pub per_prop: bool,
+
+    /// directory to write the proof of each holding property to, as
+    /// `b<i>.<ext>`, when checking several properties
+    #[arg(long)]
+    pub prop_cert_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub vacuity: VacuityConfig,
 }
 
 fn report_res(chk: &CheckConfig, res: McResult) {
@@ -67,7 +91,7 @@ fn report_res(chk: &CheckConfig, res: McResult) {
     }
 }
 
-pub fn check(mut chk: CheckConfig, cfg: EngineConfig) -> anyhow::Result<()> {
+pub fn check(mut chk: CheckConfig, mut cfg: EngineConfig) -> anyhow::Result<()> {
     if env::var("RUST_LOG").is_err() {
         unsafe { env::set_var("RUST_LOG", if chk.ui { "warn" } else { "info" }) };
     }
@@ -80,20 +104,51 @@ pub fn check(mut chk: CheckConfig, cfg: EngineConfig) -> anyhow::Result<()> {
         .canonicalize()
         .with_context(|| format!("failed to resolve model file: {}", chk.model.display()))?;
     info!("the model to be checked: {}", chk.model.display());
+    let mut report = Report::new(cfg.as_ref());
+    report.model = Some(chk.model.clone());
+    report.certificate = chk.cert.clone();
     let mut tmp_cert = None;
     if chk.cert.is_none() && (chk.certify || chk.cex) {
         let tmp_cert_file = tempfile::NamedTempFile::new().unwrap();
         chk.cert = Some(PathBuf::from(tmp_cert_file.path()));
         tmp_cert = Some(tmp_cert_file);
     }
+    if chk.cert.is_some()
+        && let EngineConfig::Kind(kind) = &cfg
+        && kind.preproc.enlarge
+    {
+        bail!(
+            "k-induction gives no certificate of an enlarged target, --enlarge cannot be used with --cert, --certify or --cex"
+        );
+    }
+    let mut frontend = frontend_from_model(&chk.model)?;
+    let bl = (!cfg.is_wl()).then(|| {
+        let (ts, symbols) = frontend.ts();
+        info!("origin ts has {}", ts.statistic());
+        report.ts = Some(TsStatistic::new(&ts));
+        (ts, symbols)
+    });
+    if let Some((ts, _)) = &bl
+        && chk.per_prop
+        && let Some(mp) = mp_config(&cfg, ts.bad.len())?
+    {
+        info!(
+            "the model has {} properties, checking them one by one with {} workers of {}",
+            ts.bad.len(),
+            mp.as_ref(),
+            cfg.as_ref()
+        );
+        cfg = mp;
+        report.engine = cfg.as_ref().to_string();
+    }
     if let EngineConfig::Portfolio(cfg) = cfg {
-        portfolio_main(chk, cfg)?;
+        let (ts, symbols) = bl.unwrap();
+        portfolio_main(chk, cfg, frontend, ts, symbols, report)?;
         drop(tmp_cert);
         return Ok(());
     }
 
     let tui = chk.ui.then(|| UiRenderer::new(cfg.as_ref())).flatten();
-    let mut frontend = frontend_from_model(&chk.model)?;
     let res = if cfg.is_wl() {
         let (wts, _symbols) = frontend.wts();
         let mut engine = create_wl_engine(cfg.clone(), wts);
@@ -105,6 +160,7 @@ pub fn check(mut chk: CheckConfig, cfg: EngineConfig) -> anyhow::Result<()> {
         let interrupt = install_interrupt_handler(engine.get_ctrl());
         let res = engine.check();
         engine.statistic();
+        engine.report(&mut report);
         if interrupt.is_interrupted() {
             if let Some(tui) = tui {
                 tui.finish(McResult::Unknown(None));
@@ -118,8 +174,10 @@ pub fn check(mut chk: CheckConfig, cfg: EngineConfig) -> anyhow::Result<()> {
         }
         res
     } else {
-        let (ts, symbols) = frontend.ts();
-        info!("origin ts has {}", ts.statistic());
+        let (ts, symbols) = bl.unwrap();
+        if cfg.is_mp() {
+            return mp_check(&chk, cfg, frontend, ts, tui, report);
+        }
         let mut engine = create_bl_engine(cfg.clone(), ts, symbols);
         engine.add_tracer(Box::new(LogTracer::new(cfg.as_ref())));
         if let Some(tui) = tui.clone() {
@@ -129,6 +187,7 @@ pub fn check(mut chk: CheckConfig, cfg: EngineConfig) -> anyhow::Result<()> {
         let interrupt = install_interrupt_handler(engine.get_ctrl());
         let res = engine.check();
         engine.statistic();
+        engine.report(&mut report);
         if interrupt.is_interrupted() {
             if let Some(tui) = tui {
                 tui.finish(McResult::Unknown(None));
@@ -146,26 +205,303 @@ pub fn check(mut chk: CheckConfig, cfg: EngineConfig) -> anyhow::Result<()> {
     if chk.certify {
         assert!(certificate_check(&chk.model, chk.cert.as_ref().unwrap()));
     }
+    report.set_result(res);
+    if chk.vacuity.vacuity {
+        check_vacuity(&chk, (!res.is_unsat()).then(Vec::new), &mut report)?;
+    }
+    write_report(&chk, report)?;
     drop(tmp_cert);
     Ok(())
 }
 
-pub fn portfolio_main(chk: CheckConfig, cfg: PortfolioConfig) -> anyhow::Result<()> {
-    let mut frontend = frontend_from_model(&chk.model)?;
-    let (ts, symbols) = frontend.ts();
-    info!("origin ts has {}", ts.statistic());
+/// PolyNexus whose workers run the bit-level engine `cfg`, to check a model
+/// with several properties one by one for `--per-prop`. A portfolio lends its
+/// preprocessing and its workers, ic3, kind and bmc preprocess the model
+/// themselves. None if `cfg` checks a single property.
+fn mp_config(cfg: &EngineConfig, num_bads: usize) -> anyhow::Result<Option<EngineConfig>> {
+    let base = match cfg {
+        EngineConfig::IC3(c) => &c.base,
+        EngineConfig::Kind(c) => &c.base,
+        EngineConfig::BMC(c) => &c.base,
+        EngineConfig::Portfolio(c) => &c.base,
+        EngineConfig::MultiProp(_) | EngineConfig::Polynexus(_) => return Ok(None),
+        _ => bail!(
+            "--per-prop needs ic3, kind, bmc or portfolio, but {} is given",
+            cfg.as_ref()
+        ),
+    };
+    if num_bads < 2 || base.prop.is_some() {
+        return Ok(None);
+    }
+    let mut mp = PolyNexusConfig::default();
+    mp.time_limit = base.time_limit;
+    if let EngineConfig::Portfolio(c) = cfg {
+        if c.share_lemma || c.worker_mem_limit.is_some() || c.mem_limit.is_some() {
+            warn!(
+                "--share-lemma, --worker-mem-limit and --mem-limit of portfolio are ignored with --per-prop"
+            );
+        }
+        mp.preproc = c.preproc.clone();
+        mp.worker_cfgs = Some(portfolio::worker_preset(
+            c.config.as_deref().unwrap_or("bl_default"),
+            c.preset.as_deref(),
+        )?);
+    } else {
+        mp.preproc.preproc = false;
+        let name = cfg.as_ref().to_lowercase();
+        mp.worker_cfgs = Some(WorkerConfigs::from_engine(&name, cfg.clone()));
+    }
+    Ok(Some(EngineConfig::Polynexus(mp)))
+}
+
+/// `--vacuity`: checks the constraints of the model, and which of them the
+/// proofs depend on. `proved` are the proved bads, or None for a single
+/// result that proves all bads, which are then checked as one property.
+fn check_vacuity(
+    chk: &CheckConfig,
+    proved: Option<Vec<usize>>,
+    report: &mut Report,
+) -> anyhow::Result<()> {
+    let (mut ts, _) = frontend_from_model(&chk.model)?.ts();
+    let combined = proved.is_none();
+    let props = proved.unwrap_or_else(|| {
+        let bad = ts.rel.new_or(take(&mut ts.bad));
+        ts.bad = LitVec::from(bad);
+        vec![0]
+    });
+    let names: Vec<_> = (0..ts.constraint.len()).map(|i| format!("c{i}")).collect();
+    let without = |i| {
+        let mut ts = ts.clone();
+        ts.constraint.remove(i);
+        ts
+    };
+    let res = vacuity::check(
+        &chk.vacuity,
+        &ts,
+        &names,
+        &props,
+        &[],
+        without,
+        PolyNexusConfig::default(),
+    )?;
+    res.print(|p| {
+        if combined {
+            "all".to_string()
+        } else {
+            format!("b{p}")
+        }
+    });
+    res.fill_report(report);
+    Ok(())
+}
+
+fn write_report(chk: &CheckConfig, mut report: Report) -> anyhow::Result<()> {
+    if let Some(path) = &chk.report {
+        report.finish();
+        report.write(path)?;
+    }
+    Ok(())
+}
+
+fn report_mp_res(chk: &CheckConfig, res: &MpMcResult) -> &'static str {
+    let overall = if res.iter().any(|r| r.is_sat()) {
+        "SAT"
+    } else if res.iter().all(|r| r.is_unsat()) {
+        "UNSAT"
+    } else {
+        "UNKNOWN"
+    };
+    println!("{overall}");
+    for (i, r) in res.iter().enumerate() {
+        let status = match r {
+            McResult::UNSAT => "UNSAT",
+            McResult::SAT(_) => "SAT",
+            McResult::Unknown(_) => "UNKNOWN",
+        };
+        println!("b{i}: {status}");
+    }
+    if chk.cex && overall == "SAT" {
+        let cex = fs::read_to_string(chk.cert.as_ref().unwrap()).unwrap();
+        println!("{cex}");
+    }
+    overall
+}
+
+/// Checks all properties with a multi-property engine. When every property
+/// holds, the certificate is the merge of the per-property proofs, otherwise
+/// it is a witness with one block per violated property.
+fn mp_check(
+    chk: &CheckConfig,
+    cfg: EngineConfig,
+    mut frontend: Box<dyn Frontend>,
+    ts: Transys,
+    tui: Option<UiRenderer>,
+    mut report: Report,
+) -> anyhow::Result<()> {
+    let ots = ts.clone();
+    let mut engine = create_mp_engine(cfg.clone(), ts)?;
+    engine.add_tracer(Box::new(LogTracer::new(cfg.as_ref())));
+    if let Some(tui) = tui.clone() {
+        engine.add_tracer(Box::new(tui.clone()));
+        engine.set_ui(tui);
+    }
+    let interrupt = install_interrupt_handler(engine.get_ctrl());
+    let res = MpEngine::check(engine.as_mut());
+    engine.statistic();
+    engine.report(&mut report);
+    if interrupt.is_interrupted() {
+        if let Some(tui) = tui {
+            tui.finish(McResult::Unknown(None));
+        }
+        exit(130);
+    }
+    let sat: Vec<_> = res
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.is_sat())
+        .map(|(p, _)| p)
+        .collect();
+    let all_unsat = res.iter().all(|r| r.is_unsat());
+    let mut cexs = Vec::new();
+    if let Some(cert_path) = &chk.cert {
+        if all_unsat {
+            let mut proof = BlProof::new(ots.clone());
+            for p in 0..ots.bad.len() {
+                let subp = engine.proof(p);
+                proof.merge(&subp, &ots);
+            }
+            let cert = frontend.bl_certificate(McBlCertificate::UNSAT(proof));
+            fs::write(cert_path, format!("{cert}")).unwrap();
+        } else if !sat.is_empty() {
+            cexs = sat.iter().map(|&p| engine.cex(p)).collect();
+            let cert = frontend.bl_mp_witness(cexs.clone());
+            fs::write(cert_path, format!("{cert}")).unwrap();
+        }
+    }
+    let overall = report_mp_res(chk, &res);
+    report.result = Some(overall.to_string());
+    report.properties = res
+        .iter()
+        .enumerate()
+        .map(|(p, &r)| {
+            let mut prop = PropReport::new(p, Some(format!("b{p}")), r);
+            if r.is_sat() || all_unsat {
+                prop.certificate = report.certificate.clone();
+            }
+            prop
+        })
+        .collect();
+    if chk.certify {
+        if all_unsat {
+            assert!(certificate_check(&chk.model, chk.cert.as_ref().unwrap()));
+        } else {
+            // each witness block is certified on its own
+            for cex in cexs {
+                let block = tempfile::NamedTempFile::new()?;
+                let cert = frontend.bl_certificate(McBlCertificate::SAT(cex));
+                fs::write(block.path(), format!("{cert}"))?;
+                assert!(certificate_check(&chk.model, block.path()));
+            }
+        }
+    }
+    if (chk.certify || chk.prop_cert_dir.is_some()) && !all_unsat {
+        let holding: Vec<_> = (0..res.len()).filter(|&p| res[p].is_unsat()).collect();
+        prove_holding(
+            chk,
+            frontend.as_mut(),
+            engine.as_mut(),
+            &holding,
+            &mut report,
+        )?;
+    }
+    if chk.vacuity.vacuity {
+        let proved = res
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.is_unsat())
+            .map(|(p, _)| p)
+            .collect();
+        check_vacuity(chk, Some(proved), &mut report)?;
+    }
+    write_report(chk, report)
+}
+
+/// Writes the proof of each of the `holding` properties of a mixed result to
+/// `--prop-cert-dir`, as the merged proof of all properties does not exist.
+/// Each proof is checked on the model restricted to its property with
+/// `--certify`.
+fn prove_holding(
+    chk: &CheckConfig,
+    frontend: &mut dyn Frontend,
+    engine: &mut dyn MpEngine,
+    holding: &[usize],
+    report: &mut Report,
+) -> anyhow::Result<()> {
+    if let Some(dir) = &chk.prop_cert_dir {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let ext = match chk.model.extension() {
+        Some(ext) if ext == "aig" || ext == "aag" => "aag",
+        _ => "btor",
+    };
+    for &p in holding {
+        let cert = frontend.bl_certificate(McBlCertificate::UNSAT(engine.proof(p)));
+        // the proof is only kept for --certify without --prop-cert-dir
+        let tmp;
+        let path = match &chk.prop_cert_dir {
+            Some(dir) => dir.join(format!("b{p}.{ext}")),
+            None => {
+                tmp = tempfile::NamedTempFile::new()?;
+                tmp.path().to_path_buf()
+            }
+        };
+        fs::write(&path, format!("{cert}"))?;
+        if chk.certify {
+            let model = frontend
+                .restrict_props(&[p])
+                .context("the model format cannot be restricted to a single property")?;
+            let model_file = tempfile::Builder::new()
+                .suffix(&format!(".{ext}"))
+                .tempfile()?;
+            fs::write(model_file.path(), format!("{model}"))?;
+            assert!(certificate_check(&model_file.path().to_path_buf(), &path));
+        }
+        if chk.prop_cert_dir.is_some() {
+            report.properties[p].certificate = Some(path);
+        }
+    }
+    Ok(())
+}
+
+pub fn portfolio_main(
+    chk: CheckConfig,
+    cfg: PortfolioConfig,
+    mut frontend: Box<dyn Frontend>,
+    ts: Transys,
+    symbols: VarSymbols,
+    mut report: Report,
+) -> anyhow::Result<()> {
     let mut engine = Portfolio::new(ts, symbols, chk.cert.is_some(), cfg)?;
     if let Some(tui) = UiRenderer::new("Portfolio") {
         engine.set_ui(tui);
     }
     // Do not register the ctrlc interrupt handler here: it spawns a background
     // thread, and Portfolio::check forks workers afterwards. Forking after
-    // threads have been spawned can deadlock in the child process.
-    // let interrupt = install_interrupt_handler(engine.get_ctrl());
+    // threads have been spawned can deadlock in the child process. Portfolio
+    // handles Ctrl-C itself with a fork-safe signal handler.
     let res = engine.check();
-    // if interrupt.is_interrupted() {
-    //     exit(130);
-    // }
+    engine.report(&mut report);
+    if engine.is_interrupted() {
+        print!("{}", engine.summary());
+        match res {
+            McResult::Unknown(Some(d)) => println!("interrupted, best bound: {d}"),
+            _ => println!("interrupted"),
+        }
+        report_res(&chk, res);
+        report.set_result(res);
+        write_report(&chk, report)?;
+        exit(130);
+    }
     if let Some(cert_path) = &chk.cert {
         let cert = engine.certificate(res);
         let cert = frontend.bl_certificate(cert);
@@ -175,5 +511,9 @@ pub fn portfolio_main(chk: CheckConfig, cfg: PortfolioConfig) -> anyhow::Result<
     if chk.certify {
         assert!(certificate_check(&chk.model, chk.cert.as_ref().unwrap()));
     }
-    Ok(())
+    report.set_result(res);
+    if chk.vacuity.vacuity {
+        check_vacuity(&chk, (!res.is_unsat()).then(Vec::new), &mut report)?;
+    }
+    write_report(&chk, report)
 }
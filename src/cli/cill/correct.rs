@@ -23,7 +23,9 @@ impl CIll {
                     cfg.preproc.scorr = false;
                     cfg.preproc.frts = false;
                     if step == 0 {
-                        cfg.end = 5;
+                        // the same depth after the last reset cycle, from
+                        // which on assertions are checked
+                        cfg.end = 5 + self.reset_cycles.saturating_sub(1);
                     } else {
                         cfg.time_limit = Some(if cfg!(debug_assertions) { 5 } else { 20 });
                         cfg.step = step;
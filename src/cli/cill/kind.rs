@@ -1,6 +1,7 @@
 use std::ops::Deref;
 
-use logicrs::{Lit, LitVec, LitVvec, Var, VarRange, satif::Satif};
+use log::error;
+use logicrs::{LitVec, LitVvec, satif::Satif};
 use rIC3::{
     BlEngine, Engine, McResult,
     transys::{
@@ -104,6 +105,7 @@ impl Engine for CIllKind {
 impl BlEngine for CIllKind {
     fn proof(&mut self) -> BlProof {
         let mut ts = self.ots.clone();
+        self.rst.add_time_witness(&mut ts);
         let eqi = self.rst.eq_invariant();
         let mut certifaiger_dnf = vec![];
         for cube in eqi {
@@ -113,112 +115,9 @@ impl BlEngine for CIllKind {
             certifaiger_dnf.push(ts.rel.new_and(lc));
         }
         certifaiger_dnf.extend(ts.bad);
-        let invariants = ts.rel.new_or(certifaiger_dnf);
-        ts.bad = LitVec::from(invariants);
-        if !ts.constraint.is_empty() {
-            ts.constraint = LitVec::from([ts.rel.new_and(ts.constraint)]);
-        }
-        let mut proof = ts.clone();
-        let ni = proof.input.len();
-        let nl = proof.latch.len();
-        let k = self.uts.num_unroll;
-        let mut inputs = proof.input.clone();
-        let mut latchs = proof.latch.clone();
-        let mut next = proof.next.clone();
-        let mut inits = proof.init.clone();
-        let mut bads = proof.bad.clone();
-        let mut constrains = proof.constraint.clone();
-        for _ in 1..k {
-            let offset = proof.max_var();
-            let map = |x: Var| {
-                if x == Var::CONST { x } else { x + offset }
-            };
-            proof.new_var_to(map(ts.max_var()));
-            let lmap = |x: Lit| Lit::new(map(x.var()), x.polarity());
-            for v in VarRange::new_inclusive(Var(1), ts.max_var()) {
-                let rel: Vec<LitVec> = ts.rel[v].iter().map(|cls| cls.map(lmap)).collect();
-                let mv = map(v);
-                proof.rel.add_rel(mv, &rel);
-            }
-            for &i in ts.input.iter() {
-                inputs.push(map(i));
-            }
-            for &l in ts.latch.iter() {
-                let ml = map(l);
-                latchs.push(ml);
-                next.insert(ml, lmap(ts.next[&l]));
-                if let Some(i) = ts.init.get(&l) {
-                    inits.insert(ml, lmap(*i));
-                }
-            }
-            bads.extend(ts.bad.map(lmap));
-            for &l in ts.constraint.iter() {
-                constrains.push(lmap(l));
-            }
-        }
-        if !constrains.is_empty() {
-            for i in 0..k {
-                bads[i] = proof.rel.new_or([bads[i], !constrains[i]]);
-            }
-        }
-        let sum = inputs.len() + latchs.len();
-        let mut aux_latchs: Vec<Lit> = Vec::new();
-        for i in 0..k {
-            let aux = proof.new_var().lit();
-            aux_latchs.push(aux);
-            let (next, init) = if i == 0 {
-                (aux, Some(Lit::constant(true)))
-            } else {
-                (aux_latchs[i - 1], Some(Lit::constant(false)))
-            };
-            proof.add_latch(aux.var(), init, next);
-        }
-        for i in 1..k {
-            for j in 0..ni {
-                proof.add_latch(inputs[j + i * ni], None, inputs[j + (i - 1) * ni].lit());
-            }
-            for j in 0..nl {
-                proof.add_latch(latchs[j + i * nl], None, latchs[j + (i - 1) * nl].lit());
-            }
-        }
-        for i in 0..k {
-            let al = aux_latchs[i];
-            let p = proof.rel.new_imply(al, !bads[i]);
-            bads[i] = !p;
-        }
-
-        for i in 1..k {
-            let al = aux_latchs[i];
-            let al_next = aux_latchs[i - 1];
-            let p = proof.rel.new_imply(al, al_next);
-            bads.push(!p);
-            let mut eqs = Vec::new();
-            let mut init = Vec::new();
-            for j in 0..nl {
-                let lis1j = latchs[(i - 1) * nl + j];
-                if let Some(&linit) = inits.get(&lis1j) {
-                    init.push(LitVec::from([lis1j.lit(), !linit]));
-                    init.push(LitVec::from([!lis1j.lit(), linit]));
-                }
-                eqs.push(
-                    proof
-                        .rel
-                        .new_xnor(next[&latchs[j + i * nl]], latchs[j + (i - 1) * nl].lit()),
-                );
-            }
-            let p = proof.rel.new_and(eqs);
-            let p = proof.rel.new_imply(al, p);
-            bads.push(!p);
-            let init: Vec<_> = init.into_iter().map(|cls| proof.rel.new_or(cls)).collect();
-            let init = proof.rel.new_and(init);
-            let p = proof.rel.new_and([!al, al_next]);
-            let p = proof.rel.new_imply(p, init);
-            bads.push(!p);
-        }
-        bads.push(!aux_latchs[0]);
-        proof.bad = LitVec::from(proof.rel.new_or(bads));
-        assert!(proof.input.len() + proof.latch.len() == sum + k);
-        BlProof { proof }
+        ts.bad = LitVec::from(ts.rel.new_or(certifaiger_dnf));
+        let k = self.uts.num_unroll.max(self.rst.eq_depth());
+        BlProof::k_induction(ts, k)
     }
 
     fn cex(&mut self) -> BlCex {
@@ -233,6 +132,13 @@ impl BlEngine for CIllKind {
         cex.bad_id = self.prop;
         cex.lift(&self.uts, Some(f));
         cex = self.rst.restore_cex(&cex);
+        // the steps of an enlarged target are completed from the cti, which
+        // gives up its lifting
+        if self.rst.enlarge() > 0 {
+            if let Err(err) = cex.exact_state(&self.ots, false, self.rst.enlarge()) {
+                error!("failed to complete the cex: {err}");
+            }
+        }
         cex
     }
 }
@@ -1,9 +1,9 @@
-mod candinv;
+pub(crate) mod candinv;
 mod correct;
 mod effective;
 mod inductive;
 mod kind;
-mod prepare;
+pub(crate) mod prepare;
 mod utils;
 
 use super::{Ric3Config, rproj::Ric3Proj};
@@ -91,6 +91,8 @@ pub struct CIll {
     dut_wts: WlTransys,
     #[allow(unused)]
     dut_wsym: WlTsSymbol,
+    /// cycles until the last reset is released
+    reset_cycles: usize,
 }
 
 impl CIll {
@@ -117,6 +119,7 @@ impl CIll {
             ts,
             ts_rst,
             bb_map,
+            reset_cycles: rcfg.reset_cycles(),
         })
     }
 }
@@ -135,7 +138,7 @@ pub fn cill(cmd: CIllCommands) -> anyhow::Result<()> {
 }
 
 fn check(rcfg: Ric3Config, rp: Ric3Proj) -> anyhow::Result<()> {
-    let dut_hash = rcfg.dut.src_hash()?;
+    let dut_hash = rcfg.dut_hash()?;
     match rp.check_cached_dut(&dut_hash)? {
         Some(false) => {
             bail!("DUT sources changed, CIll does not allow DUT changes");
@@ -135,7 +135,11 @@ fn collect_symbols(root: &mut SvModule, symbols: &BTreeMap<String, Sort>) -> any
     Ok(())
 }
 
-fn write_shadow(top: &str, symbols: &BTreeMap<String, Sort>, out_dir: &Path) -> anyhow::Result<()> {
+pub(crate) fn write_shadow(
+    top: &str,
+    symbols: &BTreeMap<String, Sort>,
+    out_dir: &Path,
+) -> anyhow::Result<()> {
     let mut root = SvModule::new(top);
     collect_symbols(&mut root, symbols)?;
     let root_str = format!("{}", root);
@@ -143,7 +147,7 @@ fn write_shadow(top: &str, symbols: &BTreeMap<String, Sort>, out_dir: &Path) ->
     Ok(())
 }
 
-fn collect_symbol_sorts(sym: &WlTsSymbol) -> anyhow::Result<BTreeMap<String, Sort>> {
+pub(crate) fn collect_symbol_sorts(sym: &WlTsSymbol) -> anyhow::Result<BTreeMap<String, Sort>> {
     let mut symbols = BTreeMap::new();
     for (term, names) in sym.signal.iter() {
         for name in names {
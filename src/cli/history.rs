@@ -0,0 +1,280 @@
+use crate::cli::rproj::Ric3Proj;
+use chrono::{DateTime, Local};
+use clap::Args;
+use rIC3::McResult;
+use ratatui::crossterm::style::Stylize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+};
+use tabled::{
+    Table, Tabled,
+    settings::{Format, Modify, Style, object::Rows},
+};
+
+#[derive(Args, Debug, Clone)]
+pub struct HistoryConfig {
+    /// Show the history of the `[[task]]` of this name
+    #[arg(long = "task")]
+    pub task: Option<String>,
+
+    /// Only show the properties whose name matches this regex
+    #[arg(long = "prop")]
+    pub prop: Option<String>,
+
+    /// Number of latest runs the trends are shown over
+    #[arg(long = "last", default_value_t = 10)]
+    pub last: usize,
+}
+
+/// The outcome of a property in a run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct PropRecord {
+    pub name: String,
+    pub res: McResult,
+    /// reached the time limit of its `[[formal.property]]`
+    pub timeout: bool,
+    /// the result is taken from a previous run
+    pub cached: bool,
+    /// solving time in seconds
+    pub time: f64,
+    /// engine of the result
+    pub engine: Option<String>,
+}
+
+impl PropRecord {
+    fn status(&self) -> &'static str {
+        match self.res {
+            McResult::UNSAT => "Proved",
+            McResult::SAT(_) => "Violated",
+            McResult::Unknown(_) if self.timeout => "Timeout",
+            McResult::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// A proved or violated property that is not any more, or a property
+    /// that is violated now.
+    pub(super) fn regressed_from(&self, before: &PropRecord) -> bool {
+        (!before.res.is_unknown() && self.res.is_unknown())
+            || (!before.res.is_sat() && self.res.is_sat())
+    }
+}
+
+/// A run in `ric3proj/history.jsonl`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct HistoryRecord {
+    pub timestamp: DateTime<Local>,
+    /// digest of the DUT sources
+    pub dut: String,
+    pub props: Vec<PropRecord>,
+}
+
+impl HistoryRecord {
+    pub(crate) fn new(dut: String, props: Vec<PropRecord>) -> Self {
+        Self {
+            timestamp: Local::now(),
+            dut,
+            props,
+        }
+    }
+
+    fn prop(&self, name: &str) -> Option<&PropRecord> {
+        self.props.iter().find(|p| p.name == name)
+    }
+}
+
+impl Ric3Proj {
+    /// Appends a run to the history, which is kept across DUT changes.
+    pub(crate) fn append_history(&self, record: &HistoryRecord) -> anyhow::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path("history.jsonl"))?;
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+        Ok(())
+    }
+
+    fn load_history(&self) -> anyhow::Result<Vec<HistoryRecord>> {
+        let path = self.path("history.jsonl");
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(path)?
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| Ok(serde_json::from_str(l)?))
+            .collect()
+    }
+}
+
+fn format_secs(secs: f64) -> String {
+    if secs < 60.0 {
+        format!("{secs:.1}s")
+    } else if secs < 3600.0 {
+        format!("{}m {}s", secs as u64 / 60, secs as u64 % 60)
+    } else {
+        format!("{}h {}m", secs as u64 / 3600, (secs as u64 % 3600) / 60)
+    }
+}
+
+/// Solving times of the runs as bars scaled to the slowest, `·` where the
+/// property was not solved in a run: cached, unknown or absent.
+fn sparkline(times: &[Option<f64>]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = times.iter().flatten().copied().fold(0.0, f64::max);
+    times
+        .iter()
+        .map(|t| match t {
+            Some(t) if max > 0.0 => BARS[((t / max) * 7.0).round() as usize],
+            Some(_) => BARS[0],
+            None => '·',
+        })
+        .collect()
+}
+
+fn print_table<T: Tabled>(rows: &[T]) {
+    let mut table = Table::new(rows);
+    table
+        .with(Style::empty())
+        .with(Modify::new(Rows::first()).with(Format::content(|s| s.yellow().bold().to_string())));
+    println!("{table}");
+}
+
+fn colored_status(p: &PropRecord) -> String {
+    match p.res {
+        McResult::UNSAT => p.status().green().to_string(),
+        McResult::SAT(_) => p.status().red().to_string(),
+        McResult::Unknown(_) => p.status().yellow().to_string(),
+    }
+}
+
+fn print_regressions(last: &HistoryRecord, prev: &HistoryRecord, filter: &Option<Regex>) {
+    #[derive(Tabled)]
+    struct Regression {
+        #[tabled(rename = "Property")]
+        property: String,
+        #[tabled(rename = "Before")]
+        before: String,
+        #[tabled(rename = "Now")]
+        now: String,
+    }
+    let rows: Vec<_> = last
+        .props
+        .iter()
+        .filter(|p| filter.as_ref().is_none_or(|f| f.is_match(&p.name)))
+        .filter_map(|p| {
+            let before = prev.prop(&p.name)?;
+            p.regressed_from(before).then(|| Regression {
+                property: p.name.clone(),
+                before: colored_status(before),
+                now: colored_status(p),
+            })
+        })
+        .collect();
+    if rows.is_empty() {
+        println!("{}", "No regressions since the previous run".green());
+        return;
+    }
+    println!(
+        "{}",
+        format!(
+            "Regressions since the previous run, DUT {} -> {}",
+            prev.dut, last.dut
+        )
+        .red()
+        .bold()
+    );
+    print_table(&rows);
+}
+
+fn print_trends(runs: &[HistoryRecord], filter: &Option<Regex>) {
+    #[derive(Tabled)]
+    struct Trend {
+        #[tabled(rename = "Property")]
+        property: String,
+        #[tabled(rename = "Status")]
+        status: String,
+        #[tabled(rename = "Engine")]
+        engine: String,
+        #[tabled(rename = "Last")]
+        last: String,
+        #[tabled(rename = "Median")]
+        median: String,
+        #[tabled(rename = "Trend")]
+        trend: String,
+    }
+    let Some(last) = runs.last() else {
+        return;
+    };
+    let mut rows = Vec::new();
+    for p in last.props.iter() {
+        if filter.as_ref().is_some_and(|f| !f.is_match(&p.name)) {
+            continue;
+        }
+        let times: Vec<_> = runs
+            .iter()
+            .map(|r| {
+                r.prop(&p.name)
+                    .filter(|p| !p.cached && !p.res.is_unknown())
+                    .map(|p| p.time)
+            })
+            .collect();
+        let mut solved: Vec<_> = times.iter().flatten().copied().collect();
+        solved.sort_by(f64::total_cmp);
+        let median = solved.get(solved.len() / 2).copied();
+        let last_time = times.last().copied().flatten();
+        // twice as slow as usual, ignoring short runs
+        let slower = last_time
+            .zip(median)
+            .is_some_and(|(l, m)| l > 1.0 && l > 2.0 * m);
+        let last_col = match last_time {
+            Some(t) => format_secs(t),
+            None if p.cached => "cached".to_string(),
+            None => "-".to_string(),
+        };
+        rows.push(Trend {
+            property: p.name.clone(),
+            status: colored_status(p),
+            engine: p.engine.clone().unwrap_or("-".to_string()),
+            last: if slower {
+                last_col.red().to_string()
+            } else {
+                last_col
+            },
+            median: median.map_or("-".to_string(), format_secs),
+            trend: sparkline(&times),
+        });
+    }
+    println!(
+        "{}",
+        format!("Solving times over the last {} runs", runs.len()).bold()
+    );
+    print_table(&rows);
+}
+
+pub fn history(cfg: HistoryConfig) -> anyhow::Result<()> {
+    let proj = match &cfg.task {
+        Some(name) => Ric3Proj::new_task(name)?,
+        None => Ric3Proj::new()?,
+    };
+    let filter = cfg.prop.as_deref().map(Regex::new).transpose()?;
+    let history = proj.load_history()?;
+    let Some(last) = history.last() else {
+        println!("No runs recorded yet");
+        return Ok(());
+    };
+    println!(
+        "{} runs, the last on {} with DUT {}",
+        history.len(),
+        last.timestamp.format("%Y-%m-%d %H:%M"),
+        last.dut
+    );
+    if let [.., prev, last] = history.as_slice() {
+        print_regressions(last, prev, &filter);
+    }
+    let runs = &history[history.len().saturating_sub(cfg.last.max(1))..];
+    print_trends(runs, &filter);
+    Ok(())
+}
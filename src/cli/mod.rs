@@ -2,9 +2,12 @@ mod build;
 mod check;
 mod cill;
 mod clean;
+mod history;
 mod rproj;
 mod run;
+mod test;
 mod trace;
+mod vacuity;
 mod vcd;
 mod verilog;
 mod yosys;
@@ -12,13 +15,18 @@ mod yosys;
 use crate::cli::{
     check::CheckConfig,
     cill::{CIllCommands, cill},
+    history::HistoryConfig,
     rproj::DutHash,
     trace::{TraceCommands, trace},
 };
 use anyhow::Context;
 use clap::{Parser, Subcommand};
 use giputils::hash::{GHashMap, GHashSet};
-use rIC3::config::EngineConfig;
+use rIC3::{
+    config::{EngineConfig, WorkerConfigs},
+    polynexus::{PropConfig, check_worker_cfgs, worker_preset},
+};
+use regex::Regex;
 use serde::Deserialize;
 use std::{
     fs,
@@ -73,6 +81,12 @@ pub enum Commands {
         #[command(subcommand)]
         cmd: TraceCommands,
     },
+
+    /// Show regressions and solving time trends of the recorded runs
+    History {
+        #[command(flatten)]
+        cfg: HistoryConfig,
+    },
 }
 
 pub fn cli_main() -> anyhow::Result<()> {
@@ -84,30 +98,147 @@ pub fn cli_main() -> anyhow::Result<()> {
         Commands::Clean => clean::clean(),
         Commands::Cill { cmd } => cill(cmd),
         Commands::Trace { cmd } => trace(cmd),
+        Commands::History { cfg } => history::history(cfg),
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Ric3Config {
     dut: Dut,
     modeling: Option<Modeling>,
     formal: Option<FormalConfig>,
+    /// named verification tasks, `[[task]]`
+    #[serde(default)]
+    task: Vec<Task>,
+}
+
+/// A verification task of the DUT, overriding the top and adding defines
+/// and top module parameters.
+#[derive(Deserialize, Debug, Clone)]
+struct Task {
+    name: String,
+    top: Option<String>,
+    #[serde(default)]
+    defines: GHashMap<String, String>,
+    #[serde(default)]
+    parameters: GHashMap<String, String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct FormalConfig {
     pub(crate) invariants: Option<PathBuf>,
+    /// PolyNexus workers, from worker names to engine arguments
+    pub(crate) portfolio: Option<GHashMap<String, String>>,
+    /// settings of individual properties, `[[formal.property]]`
+    #[serde(default)]
+    pub(crate) property: Vec<PropertyConfig>,
+    /// modules whose instances are replaced by free outputs
+    #[serde(default)]
+    pub(crate) blackbox: Vec<String>,
+    /// signals cut into free inputs
+    #[serde(default)]
+    pub(crate) cutpoints: Vec<String>,
+    /// recheck violations of the abstracted model on the full model
+    #[serde(default)]
+    pub(crate) check_full_cex: bool,
+}
+
+/// Settings of the properties whose names match `name`, an exact name or a
+/// regex.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PropertyConfig {
+    name: String,
+    /// engine arguments: `ic3`, `kind` or `bmc`, e.g. `bmc --end 20`
+    engine: Option<String>,
+    /// a PolyNexus worker preset, or a path to a TOML file with a single
+    /// preset
+    preset: Option<String>,
+    /// time limit in seconds
+    time_limit: Option<u64>,
+    /// properties of higher priority are scheduled first
+    #[serde(default)]
+    priority: i64,
+}
+
+impl PropertyConfig {
+    fn regex(&self) -> anyhow::Result<Regex> {
+        Regex::new(&format!("^(?:{})$", self.name))
+            .with_context(|| format!("invalid property pattern `{}`", self.name))
+    }
+
+    fn prop_cfg(&self) -> anyhow::Result<PropConfig> {
+        let workers = match (&self.engine, &self.preset) {
+            (Some(_), Some(_)) => anyhow::bail!("both engine and preset are set"),
+            (Some(engine), None) => Some(WorkerConfigs::from_map(GHashMap::from_iter([(
+                "engine".to_string(),
+                engine.clone(),
+            )]))?),
+            (None, Some(preset)) => Some(worker_preset(preset, None)?),
+            (None, None) => None,
+        };
+        if let Some(workers) = &workers {
+            check_worker_cfgs(workers)?;
+        }
+        Ok(PropConfig {
+            workers,
+            time_limit: self.time_limit,
+            priority: self.priority,
+        })
+    }
 }
 
 impl FormalConfig {
+    /// Whether the model is abstracted by blackboxes or cutpoints, so its
+    /// violations may be spurious.
+    pub(crate) fn is_abstract(&self) -> bool {
+        !self.blackbox.is_empty() || !self.cutpoints.is_empty()
+    }
+
     fn validate(&self) -> anyhow::Result<()> {
         if let Some(invariants) = &self.invariants
             && !invariants.exists()
         {
             anyhow::bail!("formal invariants file not found: {:?}", invariants);
         }
+        self.worker_cfgs()?;
+        self.prop_cfgs(&[])?;
         Ok(())
     }
+
+    /// Settings of the properties `props` by id, from the first matching
+    /// `[[formal.property]]`.
+    pub(crate) fn prop_cfgs(
+        &self,
+        props: &[String],
+    ) -> anyhow::Result<GHashMap<usize, PropConfig>> {
+        let mut rules = Vec::new();
+        for p in self.property.iter() {
+            let cfg = p
+                .prop_cfg()
+                .with_context(|| format!("invalid [[formal.property]] `{}`", p.name))?;
+            rules.push((p.name.as_str(), p.regex()?, cfg));
+        }
+        let mut cfgs = GHashMap::new();
+        for (id, prop) in props.iter().enumerate() {
+            if let Some((_, _, cfg)) = rules
+                .iter()
+                .find(|(name, regex, _)| *name == prop.as_str() || regex.is_match(prop))
+            {
+                cfgs.insert(id, cfg.clone());
+            }
+        }
+        Ok(cfgs)
+    }
+
+    pub(crate) fn worker_cfgs(&self) -> anyhow::Result<Option<WorkerConfigs>> {
+        let Some(portfolio) = &self.portfolio else {
+            return Ok(None);
+        };
+        let cfgs = WorkerConfigs::from_map(portfolio.clone())
+            .and_then(|cfgs| check_worker_cfgs(&cfgs).map(|_| cfgs))
+            .context("invalid [formal.portfolio]")?;
+        Ok(Some(cfgs))
+    }
 }
 
 impl Ric3Config {
@@ -131,35 +262,213 @@ impl Ric3Config {
         if let Some(formal) = &config.formal {
             formal.validate()?;
         }
+        let mut names = GHashSet::new();
+        for task in config.task.iter() {
+            if task.name.is_empty()
+                || !task
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            {
+                anyhow::bail!("invalid task name `{}`", task.name);
+            }
+            if !names.insert(task.name.as_str()) {
+                anyhow::bail!("duplicate task name `{}`", task.name);
+            }
+        }
         Ok(config)
     }
 
-    fn reset(&self) -> Option<(String, bool)> {
-        let reset = self.dut.reset.as_ref()?;
-        let (reset, polarity) = match reset.strip_prefix('!') {
-            Some(r) => (r.to_owned(), false),
-            None => (reset.to_owned(), true),
+    fn blackbox(&self) -> &[String] {
+        self.formal.as_ref().map_or(&[], |f| &f.blackbox)
+    }
+
+    /// Settings the elaborated DUT depends on besides its sources: the top
+    /// with its defines and parameters, the reset, and the blackboxes and
+    /// cutpoints.
+    fn dut_settings(&self) -> String {
+        let sorted = |map: &GHashMap<String, String>| {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort();
+            entries
         };
-        Some((reset, polarity))
+        let cutpoints = self.formal.as_ref().map_or(&[][..], |f| &f.cutpoints);
+        format!(
+            "top={} defines={:?} parameters={:?} reset={:?} blackbox={:?} cutpoints={:?}",
+            self.dut.top,
+            sorted(&self.dut.defines),
+            sorted(&self.dut.parameters),
+            self.reset_seq(),
+            self.blackbox(),
+            cutpoints
+        )
     }
+
+    /// Hash of the DUT sources and settings, for the caches of `ric3proj`.
+    fn dut_hash(&self) -> anyhow::Result<DutHash> {
+        DutHash::new(&self.dut.src(), &self.dut_settings())
+    }
+
+    /// The configuration of the task `name`.
+    fn task(&self, name: &str) -> anyhow::Result<Self> {
+        let Some(task) = self.task.iter().find(|t| t.name == name) else {
+            let known: Vec<_> = self.task.iter().map(|t| t.name.as_str()).collect();
+            anyhow::bail!("unknown task `{name}`, available: {}", known.join(", "));
+        };
+        let mut cfg = self.clone();
+        if let Some(top) = &task.top {
+            cfg.dut.top = top.clone();
+        }
+        cfg.dut.defines.extend(task.defines.clone());
+        cfg.dut.parameters.extend(task.parameters.clone());
+        Ok(cfg)
+    }
+
+    /// The tasks selected by `--task` or `--all-tasks`, in order.
+    fn select_tasks(&self, names: &[String], all: bool) -> anyhow::Result<Vec<String>> {
+        if all {
+            if self.task.is_empty() {
+                anyhow::bail!("--all-tasks given, but ric3.toml has no [[task]]");
+            }
+            return Ok(self.task.iter().map(|t| t.name.clone()).collect());
+        }
+        for name in names {
+            self.task(name)?;
+        }
+        Ok(names.to_vec())
+    }
+
+    /// Number of cycles until the last reset is released, 0 without a reset.
+    fn reset_cycles(&self) -> usize {
+        match &self.dut.reset {
+            None => 0,
+            Some(Reset::Signal(_)) => 1,
+            Some(Reset::Sequence(seq)) => seq
+                .signals
+                .iter()
+                .map(|s| seq.hold_cycles(s.strip_prefix('!').unwrap_or(s)))
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    /// `fminit -seq` signals and sequences of the reset: each reset signal is
+    /// asserted for its cycles and then released, and the reset values are
+    /// driven until the last reset is released.
+    fn reset_seq(&self) -> Vec<(String, String)> {
+        let Some(reset) = self.dut.reset.as_ref() else {
+            return Vec::new();
+        };
+        let seq = match reset {
+            Reset::Signal(signal) => ResetSequence {
+                signals: vec![signal.clone()],
+                cycles: 1,
+                hold: GHashMap::new(),
+                values: GHashMap::new(),
+            },
+            Reset::Sequence(seq) => seq.clone(),
+        };
+        let mut res = Vec::new();
+        for signal in seq.signals.iter() {
+            let (name, polarity) = match signal.strip_prefix('!') {
+                Some(r) => (r, false),
+                None => (signal.as_str(), true),
+            };
+            let n = seq.hold_cycles(name);
+            let (active, inactive) = if polarity { ("1", "0") } else { ("0", "1") };
+            let mut pattern = vec![active; n];
+            pattern.push(inactive);
+            res.push((name.to_string(), pattern.join(",")));
+        }
+        let cycles = self.reset_cycles();
+        let mut values: Vec<_> = seq.values.iter().collect();
+        values.sort();
+        for (name, value) in values {
+            res.push((name.clone(), vec![value.as_str(); cycles].join(",")));
+        }
+        res
+    }
+}
+
+/// `reset = "!rst_n"`, or a `[dut.reset]` table.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum Reset {
+    Signal(String),
+    Sequence(ResetSequence),
+}
+
+/// A multi-signal, multi-cycle reset sequence from the first cycle on.
+#[derive(Deserialize, Debug, Clone)]
+struct ResetSequence {
+    /// reset signals; prefix with "!" for an active-low reset
+    signals: Vec<String>,
+    /// number of cycles the resets are asserted
+    #[serde(default = "default_reset_cycles")]
+    cycles: usize,
+    /// number of cycles of individual reset signals, instead of `cycles`
+    #[serde(default)]
+    hold: GHashMap<String, usize>,
+    /// values driven on other inputs while in reset, as Verilog constants
+    #[serde(default)]
+    values: GHashMap<String, String>,
 }
 
-#[derive(Deserialize, Debug)]
+fn default_reset_cycles() -> usize {
+    1
+}
+
+impl ResetSequence {
+    /// Number of cycles the reset signal `name` is asserted.
+    fn hold_cycles(&self, name: &str) -> usize {
+        self.hold.get(name).copied().unwrap_or(self.cycles)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.signals.is_empty() {
+            anyhow::bail!("dut.reset signals cannot be empty");
+        }
+        let names: GHashSet<_> = self
+            .signals
+            .iter()
+            .map(|s| s.strip_prefix('!').unwrap_or(s))
+            .collect();
+        if self.cycles == 0 || self.hold.values().any(|&c| c == 0) {
+            anyhow::bail!("dut.reset cycles must be at least 1");
+        }
+        for name in self.hold.keys() {
+            if !names.contains(name.as_str()) {
+                anyhow::bail!("dut.reset hold `{name}` is not a reset signal");
+            }
+        }
+        for name in self.values.keys() {
+            if names.contains(name.as_str()) {
+                anyhow::bail!("dut.reset value `{name}` is a reset signal");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
 struct Dut {
-    reset: Option<String>,
+    reset: Option<Reset>,
     top: String,
     files: Vec<PathBuf>,
     include_files: Option<Vec<PathBuf>>,
     #[serde(default)]
     defines: GHashMap<String, String>,
+    /// top module parameters
+    #[serde(default)]
+    parameters: GHashMap<String, String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 struct Modeling {
     parser: Parse,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 #[allow(non_camel_case_types)]
 enum Parse {
     yosys,
@@ -176,14 +485,13 @@ impl Dut {
             .collect()
     }
 
-    fn src_hash(&self) -> anyhow::Result<DutHash> {
-        DutHash::new(&self.src())
-    }
-
     fn validate(&self) -> anyhow::Result<()> {
         if self.files.is_empty() {
             anyhow::bail!("dut files cannot be empty");
         }
+        if let Some(Reset::Sequence(seq)) = &self.reset {
+            seq.validate()?;
+        }
         let mut seen_names = GHashSet::new();
         let files = self.src();
         for file in files.iter() {
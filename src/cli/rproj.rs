@@ -25,6 +25,9 @@ struct FileEntry {
 #[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
 pub struct DutHash {
     files: GHashMap<PathBuf, FileEntry>,
+    /// hash of the settings the DUT is elaborated with
+    #[serde(default)]
+    settings: Vec<u8>,
 }
 
 fn calculate_hash(path: &Path) -> anyhow::Result<Vec<u8>> {
@@ -42,8 +45,11 @@ fn calculate_hash(path: &Path) -> anyhow::Result<Vec<u8>> {
 }
 
 impl DutHash {
-    pub fn new(sources: &[PathBuf]) -> anyhow::Result<Self> {
-        let mut cache = Self::default();
+    pub fn new(sources: &[PathBuf], settings: &str) -> anyhow::Result<Self> {
+        let mut cache = Self {
+            settings: Sha256::digest(settings).to_vec(),
+            ..Default::default()
+        };
         for src in sources {
             let abs_src = fs::canonicalize(src)?;
             let hash = calculate_hash(&abs_src)?;
@@ -51,6 +57,27 @@ impl DutHash {
         }
         Ok(cache)
     }
+
+    /// Short digest of the sources, by file name and content, and of the
+    /// settings, to tell DUT versions apart.
+    pub fn digest(&self) -> String {
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .map(|(path, entry)| (path.file_name().unwrap_or_default(), &entry.hash))
+            .collect();
+        files.sort();
+        let mut hasher = Sha256::new();
+        for (name, hash) in files {
+            hasher.update(name.as_encoded_bytes());
+            hasher.update(hash);
+        }
+        hasher.update(&self.settings);
+        hasher.finalize()[..6]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +92,13 @@ impl Ric3Proj {
         Ok(Self { path })
     }
 
+    /// The project of the `[[task]]` `name`, with its own cache.
+    pub fn new_task(name: &str) -> anyhow::Result<Self> {
+        let path = PathBuf::from("ric3proj").join("task").join(name);
+        fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
     pub fn path(&self, join: impl AsRef<Path>) -> PathBuf {
         self.path.join(join.as_ref())
     }
@@ -89,9 +123,19 @@ impl Ric3Proj {
     }
 
     pub fn clear_entry(&mut self, p: impl AsRef<Path>) -> anyhow::Result<()> {
+        // tasks keep their own caches, and reusable results and the history
+        // outlive the DUT
+        let keep = [
+            self.path("task"),
+            self.path("reuse"),
+            self.path("history.jsonl"),
+        ];
         for entry in fs::read_dir(p.as_ref())? {
             let entry = entry?;
             let path = entry.path();
+            if keep.contains(&path) {
+                continue;
+            }
             if entry.file_type()?.is_dir() {
                 fs::remove_dir_all(&path)?;
             } else {
@@ -139,6 +183,22 @@ impl Ric3Proj {
         Ok(())
     }
 
+    pub fn check_cached_cover_res(&self) -> anyhow::Result<Option<Vec<PropMcInfo>>> {
+        let res_path = self.path("res/cover.ron");
+        if !res_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&res_path)?;
+        let res: Vec<PropMcInfo> = ron::from_str(&content)?;
+        Ok(Some(res))
+    }
+
+    pub fn cache_cover_res(&self, res: Vec<PropMcInfo>) -> anyhow::Result<()> {
+        let cache = ron::to_string(&res)?;
+        fs::write(self.path("res/cover.ron"), cache)?;
+        Ok(())
+    }
+
     // pub fn save_term_mgr(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
     //     term_gc();
     //     self.save_serde_obj(term_mgr(), self.path(path.as_ref()))
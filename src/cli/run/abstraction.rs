@@ -0,0 +1,84 @@
+use super::PropMcState;
+use crate::cli::{Ric3Config, rproj::Ric3Proj, yosys::Yosys};
+use btor::Btor;
+use clap::Parser;
+use giputils::{file::remove_if_exists, logger::with_log_level};
+use log::LevelFilter;
+use logicrs::VarSymbols;
+use rIC3::{
+    McResult,
+    config::EngineConfig,
+    create_bl_engine,
+    frontend::{Frontend, btor::BtorFrontend},
+};
+use ratatui::crossterm::style::Stylize;
+
+/// Rechecks the violations of the abstracted model by BMC on the full model,
+/// up to the depth of each cex. A confirmed violation is no longer flagged,
+/// and one refuted by BMC up to the full depth is unknown at that depth.
+pub(crate) fn check_full_cex(
+    rcfg: &Ric3Config,
+    rp: &Ric3Proj,
+    mc: &mut [PropMcState],
+) -> anyhow::Result<()> {
+    let pending: Vec<_> = mc
+        .iter()
+        .filter(|m| m.abstracted && m.prop.res.is_sat())
+        .map(|m| m.id)
+        .collect();
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let full_dir = rp.path("full");
+    if !full_dir.join("dut.btor").exists() {
+        Yosys::generate_full_btor(rcfg, &full_dir)?;
+    }
+    let mut btorfe = BtorFrontend::new(Btor::from_file(full_dir.join("dut.btor")));
+    let (_, wsym) = btorfe.wts();
+    let (ts, _) = btorfe.ts();
+    for id in pending {
+        let m = &mut mc[id];
+        let McResult::SAT(depth) = m.prop.res else {
+            unreachable!();
+        };
+        let Some(prop) = wsym.prop.iter().position(|p| *p == m.prop.name) else {
+            continue;
+        };
+        let cfg = EngineConfig::parse_from([
+            "",
+            "bmc",
+            "--prop",
+            &prop.to_string(),
+            "--end",
+            &depth.to_string(),
+        ]);
+        let mut bmc = create_bl_engine(cfg, ts.clone(), VarSymbols::default());
+        let res = with_log_level(LevelFilter::Warn, || bmc.check());
+        if res.is_sat() {
+            println!(
+                "{} {} is violated on the full model",
+                m.label().magenta().bold(),
+                m.prop.name
+            );
+            m.abstracted = false;
+        } else if matches!(res, McResult::Unknown(Some(d)) if d >= depth) {
+            println!(
+                "{} {} has a spurious cex of the abstraction",
+                m.label().magenta().bold(),
+                m.prop.name
+            );
+            m.prop.res = McResult::Unknown(Some(depth));
+            for ext in ["wit", "vcd"] {
+                remove_if_exists(rp.path(format!("res/{}.{ext}", m.label())))?;
+            }
+            remove_if_exists(rp.path(format!("reuse/{}.cex.ron", m.label())))?;
+        } else {
+            println!(
+                "{} {} cex is not checked on the full model, BMC stopped before depth {depth}",
+                m.label().magenta().bold(),
+                m.prop.name
+            );
+        }
+    }
+    Ok(())
+}
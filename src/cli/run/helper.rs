@@ -0,0 +1,78 @@
+use super::{PropMcState, Run, RunConfig};
+use crate::cli::{
+    FormalConfig, Ric3Config,
+    cill::{
+        candinv::link_candinv,
+        prepare::{collect_symbol_sorts, write_shadow},
+    },
+    rproj::Ric3Proj,
+    yosys::Yosys,
+};
+use btor::Btor;
+use giputils::file::recreate_dir;
+use logicrs::fol::Term;
+use rIC3::{
+    frontend::btor::BtorFrontend,
+    wltransys::{WlTransys, symbol::WlTsSymbol},
+};
+
+/// Elaborates `[formal] invariants` against a shadow of the DUT and links
+/// its assertions into the DUT model, after the DUT properties.
+pub(crate) fn link_helpers(
+    rcfg: &Ric3Config,
+    rp: &Ric3Proj,
+    wts: &WlTransys,
+    wsym: &WlTsSymbol,
+) -> anyhow::Result<Option<(WlTransys, WlTsSymbol)>> {
+    let Some(invariants) = rcfg.formal.as_ref().and_then(|f| f.invariants.clone()) else {
+        return Ok(None);
+    };
+    let helper_dir = rp.path("helper");
+    recreate_dir(&helper_dir)?;
+    let symbols = collect_symbol_sorts(wsym)?;
+    write_shadow(&rcfg.dut.top, &symbols, &helper_dir)?;
+    let btor_dir = helper_dir.join("btor");
+    Yosys::generate_btor_with_files(
+        rcfg,
+        &[helper_dir.join("shadow.sv"), invariants],
+        &btor_dir,
+        "invariants",
+        true,
+    )?;
+    let mut helper_bf = BtorFrontend::new(Btor::from_file(btor_dir.join("invariants.btor")));
+    let linked = link_candinv(wts, wsym, &mut helper_bf)?;
+    Ok(Some(linked))
+}
+
+/// Proves the helper lemmas, the bads of `wts` from `nprop` on, in their own
+/// table section and returns the bads of the proven ones.
+pub(crate) fn prove_helpers(
+    rp: &Ric3Proj,
+    wts: &WlTransys,
+    wsym: &WlTsSymbol,
+    nprop: usize,
+    cfg: RunConfig,
+    formal: Option<&FormalConfig>,
+) -> anyhow::Result<Vec<Term>> {
+    let mut helper_wts = wts.clone();
+    helper_wts.bad.drain(..nprop);
+    let mut helper_wsym = wsym.clone();
+    helper_wsym.prop.drain(..nprop);
+    if helper_wts.bad.is_empty() {
+        return Ok(Vec::new());
+    }
+    let btor = helper_wts.to_btor_with_sym(&helper_wsym);
+    let mut mc = PropMcState::default_from_wts(&helper_wts, &helper_wsym);
+    for m in mc.iter_mut() {
+        m.helper = true;
+    }
+    let mut run = Run::new(btor, mc, rp.clone(), helper_wsym, cfg, formal)?;
+    run.run()?;
+    Ok(run
+        .mc
+        .iter()
+        .zip(helper_wts.bad.iter())
+        .filter(|(m, _)| m.prop.res.is_unsat())
+        .map(|(_, b)| b.clone())
+        .collect())
+}
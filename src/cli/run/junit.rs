@@ -0,0 +1,148 @@
+use super::PropMcState;
+use crate::cli::rproj::Ric3Proj;
+use rIC3::McResult;
+use std::{fmt::Write, fs, path::Path};
+
+pub(super) fn escape(s: &str) -> String {
+    let mut e = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => e.push_str("&amp;"),
+            '<' => e.push_str("&lt;"),
+            '>' => e.push_str("&gt;"),
+            '"' => e.push_str("&quot;"),
+            '\'' => e.push_str("&apos;"),
+            c => e.push(c),
+        }
+    }
+    e
+}
+
+/// Writes a JUnit XML report with one testcase per property: proved is a
+/// pass, falsified a failure with its witness and VCD, and unknown is
+/// skipped with the reached bound. Cover points are in their own testsuite,
+/// where covered is a pass with its witness and unreachable a failure.
+pub(crate) fn write_junit(
+    path: &Path,
+    mc: &[PropMcState],
+    covers: &[PropMcState],
+    proj: &Ric3Proj,
+) -> anyhow::Result<()> {
+    let all = || mc.iter().chain(covers);
+    let failures = all().filter(|m| is_failure(m)).count();
+    let skipped = all().filter(|m| m.prop.res.is_unknown()).count();
+    let time: f64 = all().map(|m| m.time.as_secs_f64()).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        xml,
+        "<testsuites name=\"ric3\" tests=\"{}\" failures=\"{failures}\" skipped=\"{skipped}\" time=\"{time:.3}\">",
+        mc.len() + covers.len()
+    )?;
+    write_suite(&mut xml, "ric3", mc, proj)?;
+    if !covers.is_empty() {
+        write_suite(&mut xml, "ric3.cover", covers, proj)?;
+    }
+    xml.push_str("</testsuites>\n");
+    fs::write(path, xml)?;
+    Ok(())
+}
+
+/// A falsified property or an unreachable cover point.
+fn is_failure(m: &PropMcState) -> bool {
+    if m.cover {
+        m.prop.res.is_unsat()
+    } else {
+        m.prop.res.is_sat()
+    }
+}
+
+/// The witness and VCD files of `m`, one per line.
+fn witness_files(m: &PropMcState, proj: &Ric3Proj) -> anyhow::Result<String> {
+    let mut body = String::new();
+    for ext in ["wit", "vcd"] {
+        let file = proj.path(format!("res/{}.{ext}", m.label()));
+        if file.exists() {
+            writeln!(body, "{ext}: {}", file.display())?;
+        }
+    }
+    Ok(body)
+}
+
+fn write_suite(
+    xml: &mut String,
+    name: &str,
+    mc: &[PropMcState],
+    proj: &Ric3Proj,
+) -> anyhow::Result<()> {
+    let failures = mc.iter().filter(|m| is_failure(m)).count();
+    let skipped = mc.iter().filter(|m| m.prop.res.is_unknown()).count();
+    let time: f64 = mc.iter().map(|m| m.time.as_secs_f64()).sum();
+    writeln!(
+        xml,
+        "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{failures}\" errors=\"0\" skipped=\"{skipped}\" time=\"{time:.3}\">",
+        mc.len()
+    )?;
+    for m in mc {
+        let mut children = Vec::new();
+        if m.cached {
+            children.push(
+                "<properties><property name=\"cached\" value=\"true\"/></properties>".to_string(),
+            );
+        }
+        let mut out = Vec::new();
+        match m.prop.res {
+            McResult::UNSAT if m.cover => {
+                children.push(
+                    "<failure type=\"unreachable\" message=\"cover point is unreachable\"/>"
+                        .to_string(),
+                );
+            }
+            McResult::UNSAT => (),
+            McResult::SAT(_) if m.cover => out.push(witness_files(m, proj)?),
+            McResult::SAT(depth) => {
+                let note = if m.abstracted {
+                    " on the abstracted model, possibly spurious"
+                } else {
+                    ""
+                };
+                children.push(format!(
+                    "<failure type=\"falsified\" message=\"property falsified at depth {depth}{note}\">{}</failure>",
+                    escape(&witness_files(m, proj)?)
+                ));
+            }
+            McResult::Unknown(bound) => {
+                let message = match bound {
+                    Some(b) => format!("unknown, reached bound {b}"),
+                    None => "unknown".to_string(),
+                };
+                children.push(format!("<skipped message=\"{message}\"/>"));
+            }
+        }
+        if m.cached {
+            out.push("cached result of a previous run\n".to_string());
+        }
+        if !out.is_empty() {
+            children.push(format!(
+                "<system-out>{}</system-out>",
+                escape(out.concat().trim_end())
+            ));
+        }
+        write!(
+            xml,
+            "    <testcase classname=\"{name}\" name=\"{}\" time=\"{:.3}\"",
+            escape(&m.prop.name),
+            m.time.as_secs_f64()
+        )?;
+        if children.is_empty() {
+            xml.push_str("/>\n");
+        } else {
+            xml.push_str(">\n");
+            for c in children {
+                writeln!(xml, "      {c}")?;
+            }
+            xml.push_str("    </testcase>\n");
+        }
+    }
+    xml.push_str("  </testsuite>\n");
+    Ok(())
+}
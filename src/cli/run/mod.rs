@@ -1,24 +1,43 @@
+mod abstraction;
+mod helper;
+mod junit;
+mod reuse;
+mod test;
 mod ui;
-
-use super::{Ric3Config, rproj::Ric3Proj, yosys::Yosys};
+mod watch;
+
+use super::{
+    FormalConfig, Ric3Config,
+    history::{HistoryRecord, PropRecord},
+    rproj::Ric3Proj,
+    vacuity::{self, Vacuity, VacuityConfig},
+    yosys::Yosys,
+};
 use crate::cli::rproj::PropMcInfo;
 use btor::Btor;
 use clap::{Args, ValueEnum};
-use giputils::file::recreate_dir;
+use giputils::{file::recreate_dir, hash::GHashMap};
 use rIC3::{
     Engine, McBlCertificate, McResult, MpEngine, MpMcResult,
+    config::WorkerConfigs,
     frontend::{Frontend, btor::BtorFrontend},
-    polynexus::{PolyNexus, PolyNexusConfig},
+    polynexus::{PolyNexus, PolyNexusConfig, PropConfig},
+    report::{PropReport, Report},
     tracer::{
         StateChannelTracerRx, WitnessChannelTracerRx, state_channel_tracer, witness_channel_tracer,
     },
-    utils::{InterruptHandle, install_interrupt_handler},
-    wltransys::{WlTransys, symbol::WlTsSymbol},
+    utils::{InterruptHandle, install_interrupt_handler, interrupt_received},
+    wltransys::{
+        WlTransys,
+        symbol::{WlTsSymbol, cut_wts_by_symbol},
+    },
 };
+use ratatui::crossterm::style::Stylize;
 use std::{
     fs::{self, File},
     io::{BufWriter, IsTerminal},
     num::NonZeroUsize,
+    path::{Path, PathBuf},
     thread::{JoinHandle, spawn},
     time::{Duration, Instant},
 };
@@ -33,6 +52,30 @@ pub struct RunConfig {
     /// Run display mode
     #[arg(long = "ui", value_enum, default_value_t = RunUi::Auto)]
     pub ui: RunUi,
+
+    /// Write a JSON report of the run
+    #[arg(long = "report")]
+    pub report: Option<PathBuf>,
+
+    /// Write a JUnit XML report with one testcase per property
+    #[arg(long = "junit")]
+    pub junit: Option<PathBuf>,
+
+    /// Run the `[[task]]` of this name, may be repeated
+    #[arg(long = "task")]
+    pub task: Vec<String>,
+
+    /// Run all `[[task]]` entries
+    #[arg(long = "all-tasks", conflicts_with = "task")]
+    pub all_tasks: bool,
+
+    /// Keep running and re-verify the changed properties whenever a DUT
+    /// source changes, until Ctrl-C
+    #[arg(long = "watch", conflicts_with_all = ["all_tasks", "vacuity"])]
+    pub watch: bool,
+
+    #[command(flatten)]
+    pub vacuity: VacuityConfig,
 }
 
 #[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -51,6 +94,8 @@ pub enum McStatus {
     Solving,
     Pause,
     Wait,
+    /// reached the time limit of its `[[formal.property]]`
+    Timeout,
 }
 
 #[derive(Debug)]
@@ -60,6 +105,15 @@ pub(crate) struct PropMcState {
     pub state: McStatus,
     pub start_time: Option<Instant>,
     pub time: Duration,
+    /// the result is taken from a previous run
+    pub cached: bool,
+    /// a cover point, SAT is covered and UNSAT unreachable
+    pub cover: bool,
+    /// a helper lemma of `[formal] invariants`
+    pub helper: bool,
+    /// checked on a model abstracted by blackboxes or cutpoints, so a
+    /// violation may be spurious
+    pub abstracted: bool,
 }
 
 impl PropMcState {
@@ -75,6 +129,10 @@ impl PropMcState {
                 state: McStatus::Wait,
                 start_time: None,
                 time: Duration::ZERO,
+                cached: false,
+                cover: false,
+                helper: false,
+                abstracted: false,
             })
             .collect()
     }
@@ -87,10 +145,34 @@ impl PropMcState {
         let mut mc = Self::default_from_wts(wts, symbols);
         for prop in cached {
             let id = symbols.prop_index_by_name(&prop.name);
+            mc[id].cached = !prop.res.is_unknown();
             mc[id].prop = prop;
         }
         mc
     }
+
+    /// Result label, `p` for properties, `c` for cover points and `h` for
+    /// helper lemmas.
+    pub(crate) fn label(&self) -> String {
+        if self.cover {
+            format!("c{}", self.id)
+        } else if self.helper {
+            format!("h{}", self.id)
+        } else {
+            format!("p{}", self.id)
+        }
+    }
+
+    /// Header of the table section this entry is shown in.
+    pub(crate) fn section(&self) -> &'static str {
+        if self.cover {
+            "Cover"
+        } else if self.helper {
+            "Helper"
+        } else {
+            "Property"
+        }
+    }
 }
 
 /// PolyNexus task handle
@@ -98,7 +180,6 @@ pub(crate) struct NexusTask {
     join: JoinHandle<(MpMcResult, PolyNexus)>,
     state_trx: StateChannelTracerRx,
     wit_trx: WitnessChannelTracerRx,
-    #[allow(unused)]
     ctrl: InterruptHandle,
 }
 
@@ -111,6 +192,10 @@ pub(crate) struct Run {
     mc: Vec<PropMcState>,
     nexus_task: Option<NexusTask>,
     cfg: RunConfig,
+    /// PolyNexus workers of `[formal.portfolio]`
+    worker_cfgs: Option<WorkerConfigs>,
+    /// settings of `[[formal.property]]`, by property id
+    prop_cfgs: GHashMap<usize, PropConfig>,
 }
 
 #[derive(Debug, Default)]
@@ -127,9 +212,15 @@ impl Run {
         ric3_proj: Ric3Proj,
         wsym: WlTsSymbol,
         cfg: RunConfig,
+        formal: Option<&FormalConfig>,
     ) -> anyhow::Result<Self> {
+        let (worker_cfgs, prop_cfgs) = match formal {
+            Some(formal) => (formal.worker_cfgs()?, formal.prop_cfgs(&wsym.prop)?),
+            None => Default::default(),
+        };
         let btorfe = BtorFrontend::new(btor.clone());
         fs::create_dir_all(ric3_proj.path("res"))?;
+        fs::create_dir_all(ric3_proj.path("reuse"))?;
         recreate_dir(ric3_proj.path("tmp"))?;
         Ok(Self {
             btor,
@@ -139,11 +230,13 @@ impl Run {
             mc,
             nexus_task: None,
             cfg,
+            worker_cfgs,
+            prop_cfgs,
         })
     }
 
     pub(crate) fn run(&mut self) -> anyhow::Result<()> {
-        self.launch_nexus();
+        self.launch_nexus()?;
         match self.cfg.ui.resolve() {
             RunUi::Tui => self.run_tui(),
             RunUi::Plain => self.run_plain(),
@@ -169,11 +262,12 @@ impl Run {
                 wit_trx,
                 ..
             } = task;
-            let (res, _engine) = join
+            let (res, mut engine) = join
                 .join()
                 .map_err(|_| anyhow::anyhow!("PolyNexus worker thread panicked"))?;
             self.drain_nexus_channels(&state_trx, &wit_trx, &mut updates)?;
-            self.apply_nexus_results(&res, &mut updates);
+            self.apply_nexus_results(&res, &engine, &mut updates);
+            self.save_proofs(&mut engine)?;
             updates.finished = true;
         } else {
             self.nexus_task = Some(task);
@@ -182,7 +276,7 @@ impl Run {
         Ok(updates)
     }
 
-    pub(crate) fn launch_nexus(&mut self) {
+    pub(crate) fn launch_nexus(&mut self) -> anyhow::Result<()> {
         let pending: Vec<usize> = self
             .mc
             .iter()
@@ -191,16 +285,18 @@ impl Run {
             .collect();
 
         if pending.is_empty() {
-            return;
+            return Ok(());
         }
 
         let (ts, _) = self.btorfe.ts();
         let cfg = PolyNexusConfig {
             workers: self.cfg.workers.map(|workers| workers.get()),
+            worker_cfgs: self.worker_cfgs.clone(),
+            prop_cfgs: self.prop_cfgs.clone(),
             ..Default::default()
         };
         let mp_res: MpMcResult = self.mc.iter().map(|m| m.prop.res).collect();
-        let mut engine = PolyNexus::new(cfg, ts, mp_res);
+        let mut engine = PolyNexus::new(cfg, ts, mp_res)?;
 
         let (state_tsx, state_trx) = state_channel_tracer();
         let (wit_tsx, wit_trx) = witness_channel_tracer();
@@ -226,6 +322,7 @@ impl Run {
             wit_trx,
             ctrl,
         });
+        Ok(())
     }
 
     pub(crate) fn all_done(&self) -> bool {
@@ -265,14 +362,19 @@ impl Run {
         while let std::result::Result::Ok(cex) = wit_trx.try_recv() {
             let cex = cex.into_sat().unwrap();
             let prop_id = cex.bad_id;
+            let label = self.mc[prop_id].label();
             let cex = self.btorfe.bl_certificate(McBlCertificate::SAT(cex));
-            let wit_path = self.ric3_proj.path(format!("res/p{prop_id}.wit"));
+            let wit_path = self.ric3_proj.path(format!("res/{label}.wit"));
             let wit = cex.to_string();
             fs::write(&wit_path, &wit)?;
 
             let mut cex = self.btorfe.deserialize_wl_unsafe_certificate(wit);
+            let m = &self.mc[prop_id];
+            if !m.cover && !m.helper {
+                reuse::save_cex(&self.ric3_proj, &label, &m.prop.name, &cex, &self.wsym)?;
+            }
             cex.enrich(&self.wsym.keys().cloned().collect());
-            let vcd_path = self.ric3_proj.path(format!("res/p{prop_id}.vcd"));
+            let vcd_path = self.ric3_proj.path(format!("res/{label}.vcd"));
             let vcd_file = BufWriter::new(File::create(vcd_path)?);
             crate::cli::vcd::wlwitness_vcd(&cex, &self.wsym, vcd_file, "")?;
             updates.witness.push(prop_id);
@@ -280,12 +382,123 @@ impl Run {
         Ok(())
     }
 
-    fn apply_nexus_results(&mut self, res: &MpMcResult, updates: &mut RunUpdates) {
+    /// Keeps the invariants of the properties proved by `engine`, to be
+    /// re-checked after DUT changes.
+    fn save_proofs(&self, engine: &mut PolyNexus) -> anyhow::Result<()> {
+        for m in self.mc.iter() {
+            if m.cover || m.helper || !m.prop.res.is_unsat() || !engine.has_proof(m.id) {
+                continue;
+            }
+            let proof = self.btorfe.wl_proof(&MpEngine::proof(engine, m.id));
+            reuse::save_proof(&self.ric3_proj, &m.label(), &m.prop.name, proof, &self.wsym)?;
+        }
+        Ok(())
+    }
+
+    /// Appends the property results of this run to the history.
+    fn record_history(&self, ric3_cfg: &Ric3Config) -> anyhow::Result<()> {
+        let props = self
+            .mc
+            .iter()
+            .map(|m| PropRecord {
+                name: m.prop.name.clone(),
+                res: m.prop.res,
+                timeout: m.state == McStatus::Timeout,
+                cached: m.cached,
+                time: m.time.as_secs_f64(),
+                engine: m
+                    .prop
+                    .config
+                    .as_ref()
+                    .map(|c| AsRef::<str>::as_ref(c).to_string()),
+            })
+            .collect();
+        let dut = ric3_cfg.dut_hash()?.digest();
+        self.ric3_proj
+            .append_history(&HistoryRecord::new(dut, props))
+    }
+
+    /// Checks the assumptions of the DUT, and which of them the proof of
+    /// each proved property depends on. With helper lemmas, the checks are
+    /// on the `linked` model, whose proven lemmas are re-proved along.
+    fn check_vacuity(
+        &self,
+        ric3_cfg: &Ric3Config,
+        linked: Option<(WlTransys, WlTsSymbol, Vec<usize>)>,
+    ) -> anyhow::Result<Vacuity> {
+        let (wts, wsym, lemmas) = match linked {
+            Some(linked) => linked,
+            None => {
+                let (_, wts, wsym) = read_model(ric3_cfg, &self.ric3_proj)?;
+                (wts, wsym, Vec::new())
+            }
+        };
+        let (ts, _) = BtorFrontend::new(wts.to_btor_with_sym(&wsym)).ts();
+        let names = vacuity::assumption_names(&wts, &wsym);
+        let props: Vec<_> = self
+            .mc
+            .iter()
+            .filter(|m| m.prop.res.is_unsat())
+            .map(|m| m.id)
+            .collect();
+        let without = |i| {
+            let mut wts = wts.clone();
+            wts.constraint.remove(i);
+            BtorFrontend::new(wts.to_btor_with_sym(&wsym)).ts().0
+        };
+        let nexus = PolyNexusConfig {
+            workers: self.cfg.workers.map(|workers| workers.get()),
+            worker_cfgs: self.worker_cfgs.clone(),
+            ..Default::default()
+        };
+        vacuity::check(
+            &self.cfg.vacuity,
+            &ts,
+            &names,
+            &props,
+            &lemmas,
+            without,
+            nexus,
+        )
+    }
+
+    /// Adds the results of the properties and of the cover points `covers`,
+    /// with the witness of each violated property and covered point.
+    fn fill_report(&self, report: &mut Report, covers: &[PropMcState]) {
+        let prop_report = |m: &PropMcState| {
+            let mut prop = PropReport::new(m.id, Some(m.prop.name.clone()), m.prop.res);
+            if m.prop.res.is_sat() {
+                prop.certificate = Some(self.ric3_proj.path(format!("res/{}.wit", m.label())));
+            }
+            prop
+        };
+        report.properties = self.mc.iter().map(prop_report).collect();
+        report.covers = covers.iter().map(prop_report).collect();
+        let overall = if self.mc.iter().any(|m| m.prop.res.is_sat()) {
+            "SAT"
+        } else if self.mc.iter().all(|m| m.prop.res.is_unsat()) {
+            "UNSAT"
+        } else {
+            "UNKNOWN"
+        };
+        report.result = Some(overall.to_string());
+    }
+
+    fn apply_nexus_results(
+        &mut self,
+        res: &MpMcResult,
+        engine: &PolyNexus,
+        updates: &mut RunUpdates,
+    ) {
         for (id, result) in res.iter().copied().enumerate() {
+            let time_limit = self.prop_cfgs.get(&id).and_then(|c| c.time_limit);
             let prop = &mut self.mc[id];
             let old_result = prop.prop.res;
             let old_state = prop.state;
             prop.prop.res = result;
+            if let Some(config) = engine.solved_by(id) {
+                prop.prop.config = Some(config.clone());
+            }
             if matches!(result, McResult::Unknown(_)) {
                 prop.state = McStatus::Pause;
             } else {
@@ -299,6 +512,11 @@ impl Run {
             if old_state != McStatus::Solving && prop.state == McStatus::Solving {
                 prop.start_time = Some(Instant::now());
             }
+            if prop.state == McStatus::Pause
+                && time_limit.is_some_and(|t| prop.time >= Duration::from_secs(t))
+            {
+                prop.state = McStatus::Timeout;
+            }
             let changed = if matches!(result, McResult::Unknown(_)) {
                 old_result != result || old_state != prop.state
             } else {
@@ -311,6 +529,37 @@ impl Run {
     }
 }
 
+/// Checks the reachability of the cover points, in their own table section,
+/// and returns their results.
+fn run_covers(
+    ric3_proj: &Ric3Proj,
+    cfg: RunConfig,
+    formal: Option<&FormalConfig>,
+) -> anyhow::Result<Vec<PropMcState>> {
+    let cover_path = ric3_proj.path("dut/dut_cover.btor");
+    if !cover_path.exists() {
+        return Ok(Vec::new());
+    }
+    let btor = Btor::from_file(cover_path);
+    let mut btorfe = BtorFrontend::new(btor.clone());
+    let (wts, symbol) = btorfe.wts();
+    if wts.bad.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut mc = ric3_proj
+        .check_cached_cover_res()?
+        .map(|p| PropMcState::from_cached_res(&wts, &symbol, p))
+        .unwrap_or(PropMcState::default_from_wts(&wts, &symbol));
+    for m in mc.iter_mut() {
+        m.cover = true;
+    }
+    let mut run = Run::new(btor, mc, ric3_proj.clone(), symbol, cfg, formal)?;
+    run.run()?;
+    let res: Vec<_> = run.mc.iter().map(|l| l.prop.clone()).collect();
+    run.ric3_proj.cache_cover_res(res)?;
+    Ok(run.mc)
+}
+
 impl RunUi {
     fn resolve(self) -> Self {
         match self {
@@ -323,24 +572,184 @@ impl RunUi {
 
 pub fn run(cfg: RunConfig) -> anyhow::Result<()> {
     let ric3_cfg = Ric3Config::from_file("ric3.toml")?;
-    let mut ric3_proj = Ric3Proj::new()?;
-    let dut_hash = ric3_cfg.dut.src_hash()?;
+    let tasks = ric3_cfg.select_tasks(&cfg.task, cfg.all_tasks)?;
+    if cfg.watch {
+        return match tasks.as_slice() {
+            [] => watch::watch(None, Ric3Proj::new()?, cfg),
+            [name] => watch::watch(Some(name), Ric3Proj::new_task(name)?, cfg),
+            _ => anyhow::bail!("--watch runs a single task"),
+        };
+    }
+    if tasks.is_empty() {
+        run_task(&ric3_cfg, Ric3Proj::new()?, cfg, None)?;
+        return Ok(());
+    }
+    let mut summary = Vec::new();
+    for name in tasks {
+        println!("{}", format!("Task {name}").bold());
+        let task_cfg = ric3_cfg.task(&name)?;
+        let mc = run_task(
+            &task_cfg,
+            Ric3Proj::new_task(&name)?,
+            cfg.clone(),
+            Some(&name),
+        )?;
+        summary.push((name, mc));
+        if interrupt_received() {
+            break;
+        }
+    }
+    ui::print_task_summary(&summary);
+    Ok(())
+}
+
+/// `path` of the task `name`, e.g. `report.json` becomes `report.<name>.json`.
+fn task_path(path: &Path, task: Option<&str>) -> PathBuf {
+    let Some(task) = task else {
+        return path.to_path_buf();
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{stem}.{task}.{}", ext.to_string_lossy())),
+        None => path.with_file_name(format!("{stem}.{task}")),
+    }
+}
+
+/// Elaborates the DUT into `ric3proj/dut`, unless it is cached for the
+/// current sources, and returns whether it did. A new elaboration clears the
+/// cached results, except the reusable ones.
+fn load_dut(ric3_cfg: &Ric3Config, ric3_proj: &mut Ric3Proj) -> anyhow::Result<bool> {
+    let dut_hash = ric3_cfg.dut_hash()?;
     let cached = ric3_proj.check_cached_dut(&dut_hash)?;
-    if cached.is_none_or(|c| !c) {
-        ric3_proj.clear()?;
-        Yosys::generate_btor(&ric3_cfg, ric3_proj.path("dut"))?;
-        ric3_proj.cache_dut(&dut_hash)?;
+    if cached.is_some_and(|c| c) {
+        return Ok(false);
     }
-    let btor = Btor::from_file(ric3_proj.path("dut/dut.btor"));
+    ric3_proj.clear()?;
+    Yosys::generate_btor(ric3_cfg, ric3_proj.path("dut"))?;
+    ric3_proj.cache_dut(&dut_hash)?;
+    Ok(true)
+}
+
+/// Reads the elaborated DUT, with the `[formal] cutpoints` applied.
+fn read_model(
+    ric3_cfg: &Ric3Config,
+    ric3_proj: &Ric3Proj,
+) -> anyhow::Result<(Btor, WlTransys, WlTsSymbol)> {
+    let mut btor = Btor::from_file(ric3_proj.path("dut/dut.btor"));
     let mut btorfe = BtorFrontend::new(btor.clone());
-    let (wts, symbol) = btorfe.wts();
-    let mc = ric3_proj
+    let (mut wts, mut symbol) = btorfe.wts();
+    if let Some(formal) = ric3_cfg.formal.as_ref()
+        && !formal.cutpoints.is_empty()
+    {
+        cut_wts_by_symbol(&mut wts, &mut symbol, &formal.cutpoints)?;
+        btor = wts.to_btor_with_sym(&symbol);
+    }
+    Ok((btor, wts, symbol))
+}
+
+/// Property states of the model, with the cached results.
+fn cached_mc(
+    ric3_cfg: &Ric3Config,
+    ric3_proj: &Ric3Proj,
+    wts: &WlTransys,
+    symbol: &WlTsSymbol,
+) -> anyhow::Result<Vec<PropMcState>> {
+    let mut mc = ric3_proj
         .check_cached_res()?
-        .map(|p| PropMcState::from_cached_res(&wts, &symbol, p))
-        .unwrap_or(PropMcState::default_from_wts(&wts, &symbol));
-    let mut run = Run::new(btor, mc, ric3_proj, symbol, cfg)?;
-    run.run()?;
-    let res: Vec<_> = run.mc.iter().map(|l| l.prop.clone()).collect();
-    run.ric3_proj.cache_res(res)?;
-    Ok(())
+        .map(|p| PropMcState::from_cached_res(wts, symbol, p))
+        .unwrap_or(PropMcState::default_from_wts(wts, symbol));
+    let abstracted = ric3_cfg
+        .formal
+        .as_ref()
+        .is_some_and(FormalConfig::is_abstract);
+    for m in mc.iter_mut() {
+        m.abstracted = abstracted;
+    }
+    Ok(mc)
+}
+
+/// Runs a single task, or the `[dut]` when `task` is None, and returns its
+/// property results.
+fn run_task(
+    ric3_cfg: &Ric3Config,
+    mut ric3_proj: Ric3Proj,
+    cfg: RunConfig,
+    task: Option<&str>,
+) -> anyhow::Result<Vec<PropMcState>> {
+    let mut report = Report::new("polynexus");
+    let elaborated = load_dut(ric3_cfg, &mut ric3_proj)?;
+    let (mut btor, wts, mut symbol) = read_model(ric3_cfg, &ric3_proj)?;
+    let formal = ric3_cfg.formal.as_ref();
+    let mut mc = cached_mc(ric3_cfg, &ric3_proj, &wts, &symbol)?;
+    if elaborated {
+        let reused = reuse::recheck(&ric3_proj, &btor, &wts, &symbol, &mut mc)?;
+        if reused > 0 {
+            println!(
+                "{}",
+                format!("Reused {reused} results of the previous DUT").bold()
+            );
+        }
+    }
+    // the linked model with the ids of the proven lemmas, for the vacuity
+    // checks
+    let mut linked = None;
+    if mc.iter().any(|m| m.prop.res.is_unknown())
+        && let Some((mut linked_wts, mut linked_sym)) =
+            helper::link_helpers(ric3_cfg, &ric3_proj, &wts, &symbol)?
+    {
+        let nprop = wts.bad.len();
+        let lemmas = helper::prove_helpers(
+            &ric3_proj,
+            &linked_wts,
+            &linked_sym,
+            nprop,
+            cfg.clone(),
+            formal,
+        )?;
+        let proven: Vec<_> = (nprop..linked_wts.bad.len())
+            .filter(|&i| lemmas.contains(&linked_wts.bad[i]))
+            .collect();
+        linked = Some((linked_wts.clone(), linked_sym.clone(), proven));
+        linked_wts.bad.truncate(nprop);
+        linked_sym.prop.truncate(nprop);
+        linked_wts.constraint.extend(lemmas.iter().map(|l| !l));
+        btor = linked_wts.to_btor_with_sym(&linked_sym);
+        symbol = linked_sym;
+    }
+    let report_path = cfg.report.clone();
+    let junit_path = cfg.junit.clone();
+    let mut run = Run::new(btor, mc, ric3_proj, symbol, cfg.clone(), formal)?;
+    if !interrupt_received() {
+        run.run()?;
+        if formal.is_some_and(|f| f.check_full_cex) && !interrupt_received() {
+            abstraction::check_full_cex(ric3_cfg, &run.ric3_proj, &mut run.mc)?;
+        }
+        let res: Vec<_> = run.mc.iter().map(|l| l.prop.clone()).collect();
+        run.ric3_proj.cache_res(res)?;
+        if !interrupt_received() {
+            run.record_history(ric3_cfg)?;
+        }
+    }
+    let mut covers = Vec::new();
+    if !interrupt_received() {
+        covers = run_covers(&run.ric3_proj, cfg, formal)?;
+    }
+    let mut vacuity = None;
+    if run.cfg.vacuity.vacuity && !interrupt_received() {
+        let v = run.check_vacuity(ric3_cfg, linked)?;
+        v.print(|id| run.mc[id].prop.name.clone());
+        vacuity = Some(v);
+    }
+    if let Some(path) = report_path {
+        run.fill_report(&mut report, &covers);
+        if let Some(vacuity) = vacuity {
+            vacuity.fill_report(&mut report);
+        }
+        report.finish();
+        report.write(&task_path(&path, task))?;
+    }
+    if let Some(path) = junit_path {
+        junit::write_junit(&task_path(&path, task), &run.mc, &covers, &run.ric3_proj)?;
+    }
+    Ok(run.mc)
 }
@@ -0,0 +1,269 @@
+use super::PropMcState;
+use crate::cli::{rproj::Ric3Proj, vcd::wlwitness_vcd};
+use btor::Btor;
+use giputils::{file::recreate_dir, hash::GHashMap};
+use logicrs::{
+    LboolVec, LitVec,
+    fol::{Sort, Term, Value},
+};
+use rIC3::{
+    McResult, McWlCertificate,
+    frontend::{Frontend, btor::BtorFrontend},
+    transys::certify::BlCex,
+    wltransys::{
+        WlTransys,
+        bitblast::BitblastMap,
+        cert::{WlCex, WlProof},
+        symbol::{WlTsSymbol, link_wts_by_symbol},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    mem::take,
+    panic,
+    path::Path,
+};
+
+/// A cex over RTL signal names, to be replayed on a re-elaborated DUT.
+#[derive(Serialize, Deserialize)]
+pub(super) struct NamedCex {
+    prop: String,
+    /// values of the named latches in the first step
+    init: Vec<(String, String)>,
+    /// values of the named inputs in each step
+    input: Vec<Vec<(String, String)>>,
+}
+
+impl NamedCex {
+    fn new(prop: &str, cex: &WlCex, wsym: &WlTsSymbol) -> Self {
+        let names: GHashMap<Term, String> = wsym
+            .signal
+            .iter()
+            .filter_map(|(t, n)| n.iter().next().map(|n| (t.clone(), n.clone())))
+            .collect();
+        let init = cex
+            .state
+            .first()
+            .into_iter()
+            .flatten()
+            .filter_map(|tv| {
+                let Value::Bv(bv) = tv.v() else {
+                    return None;
+                };
+                Some((names.get(tv.t())?.clone(), format!("{bv:b}")))
+            })
+            .collect();
+        let input = cex
+            .input
+            .iter()
+            .map(|step| {
+                step.iter()
+                    .filter_map(|tv| Some((names.get(tv.t())?.clone(), format!("{:b}", tv.v()))))
+                    .collect()
+            })
+            .collect();
+        Self {
+            prop: prop.to_string(),
+            init,
+            input,
+        }
+    }
+
+    /// The cex on the bitblasted `wts`, with the values of the signals that
+    /// are still there with the same width.
+    pub(super) fn bitblast(&self, wts: &WlTransys, wsym: &WlTsSymbol, bb: &BitblastMap) -> BlCex {
+        let lits = |values: &[(String, String)], vars: &[Term]| {
+            let mut lits = LitVec::new();
+            for (name, bits) in values {
+                if let Some(term) = wsym.term_of_sym(name)
+                    && vars.contains(term)
+                    && term.sort() == Sort::Bv(bits.len())
+                {
+                    let value = Value::Bv(LboolVec::from(bits.as_str()));
+                    lits.extend(bb.map_value(term, &value).iter().copied());
+                }
+            }
+            lits
+        };
+        let mut cex = BlCex::new();
+        for step in self.input.iter() {
+            cex.input.push(lits(step, &wts.input));
+            cex.state.push(LitVec::new());
+        }
+        if let Some(state) = cex.state.first_mut() {
+            *state = lits(&self.init, &wts.latch);
+        }
+        cex
+    }
+}
+
+/// Keeps the cex of `prop` by signal names.
+pub(crate) fn save_cex(
+    rp: &Ric3Proj,
+    label: &str,
+    prop: &str,
+    cex: &WlCex,
+    wsym: &WlTsSymbol,
+) -> anyhow::Result<()> {
+    rp.save_serde_obj(
+        &NamedCex::new(prop, cex, wsym),
+        format!("reuse/{label}.cex.ron"),
+    )
+}
+
+/// Keeps the invariant of the proof of `prop`: the extra latches of the proof
+/// and its bad, over the named signals of the model. The model constraints
+/// are left out, so that a re-check uses the constraints of the new DUT.
+pub(crate) fn save_proof(
+    rp: &Ric3Proj,
+    label: &str,
+    prop: &str,
+    proof: WlProof,
+    wsym: &WlTsSymbol,
+) -> anyhow::Result<()> {
+    let mut cert = proof.proof;
+    let bad = Term::new_ors(take(&mut cert.bad));
+    cert.bad = vec![bad];
+    cert.constraint.clear();
+    cert.output.clear();
+    cert.justice.clear();
+    let mut sym = wsym.clone();
+    sym.prop = vec![prop.to_string()];
+    let btor = cert.to_btor_with_sym(&sym);
+    fs::write(rp.path(format!("reuse/{label}.inv.btor")), btor.to_string())?;
+    Ok(())
+}
+
+/// Writes the witness and VCD of a cex of the DUT `btor`.
+fn write_witness(
+    rp: &Ric3Proj,
+    label: &str,
+    btor: &Btor,
+    wsym: &WlTsSymbol,
+    cex: WlCex,
+) -> anyhow::Result<()> {
+    let mut btorfe = BtorFrontend::new(btor.clone());
+    let wit = btorfe.wl_certificate(McWlCertificate::SAT(cex)).to_string();
+    fs::create_dir_all(rp.path("res"))?;
+    fs::write(rp.path(format!("res/{label}.wit")), &wit)?;
+    let mut cex = btorfe.deserialize_wl_unsafe_certificate(wit);
+    cex.enrich(&wsym.keys().cloned().collect());
+    let vcd_file = BufWriter::new(File::create(rp.path(format!("res/{label}.vcd")))?);
+    wlwitness_vcd(&cex, wsym, vcd_file, "")?;
+    Ok(())
+}
+
+/// Whether the invariant `cert` still proves bad `prop` of `wts`, linked by
+/// signal names: the property and the invariant together are 1-inductive.
+fn check_proof(
+    wts: &WlTransys,
+    wsym: &WlTsSymbol,
+    prop: usize,
+    cert: &(WlTransys, WlTsSymbol),
+) -> bool {
+    let mut model = wts.clone();
+    model.bad = vec![wts.bad[prop].clone()];
+    let Ok((linked, _, _)) = link_wts_by_symbol(&model, wsym, cert.0.clone(), cert.1.clone())
+    else {
+        return false;
+    };
+    let (ts, _) = linked.bitblast_to_ts();
+    ts.is_inductive()
+}
+
+/// Reads the kept invariant `path`, or `None` if it is not a valid BTOR.
+fn load_proof(path: &Path) -> Option<(String, (WlTransys, WlTsSymbol))> {
+    let text = fs::read_to_string(path).ok()?;
+    let cert = panic::catch_unwind(|| BtorFrontend::new(Btor::from_file(path)).wts()).ok()?;
+    Some((text, cert))
+}
+
+/// Re-verifies the kept results on the re-elaborated DUT `btor`, for the
+/// unresolved entries of `mc`: a cex that still reaches its bad confirms the
+/// violation, and an invariant that is still inductive proves the property.
+/// Results that do not hold any more are dropped, and unreadable entries are
+/// skipped. The kept results are written into a new directory that replaces
+/// `reuse/` only once all of them are re-verified. Returns the number of
+/// reused results.
+pub(crate) fn recheck(
+    rp: &Ric3Proj,
+    btor: &Btor,
+    wts: &WlTransys,
+    wsym: &WlTsSymbol,
+    mc: &mut [PropMcState],
+) -> anyhow::Result<usize> {
+    let dir = rp.path("reuse");
+    fs::create_dir_all(&dir)?;
+    let mut proofs = GHashMap::new();
+    let mut cexs = GHashMap::new();
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let path = entry.path();
+        let file = path.file_name().unwrap_or_default().to_string_lossy();
+        if file.ends_with(".inv.btor") {
+            if let Some((text, cert)) = load_proof(&path)
+                && let [prop] = cert.1.prop.as_slice()
+            {
+                proofs.insert(prop.clone(), (text, cert));
+            }
+        } else if file.ends_with(".cex.ron")
+            && let Ok(text) = fs::read_to_string(&path)
+            && let Ok(cex) = ron::from_str::<NamedCex>(&text)
+        {
+            cexs.insert(cex.prop.clone(), cex);
+        }
+    }
+    if proofs.is_empty() && cexs.is_empty() {
+        return Ok(0);
+    }
+    let (ts, bb) = wts.bitblast_to_ts();
+    let mut kept = Vec::new();
+    let mut reused = 0;
+    for m in mc.iter_mut() {
+        let label = m.label();
+        let cex = cexs.get(&m.prop.name);
+        let proof = proofs.get(&m.prop.name);
+        match m.prop.res {
+            // resolved already, only its kept result is carried over
+            McResult::SAT(_) => {
+                if let Some(cex) = cex {
+                    kept.push((format!("{label}.cex.ron"), ron::to_string(cex)?));
+                }
+            }
+            McResult::UNSAT => {
+                if let Some((text, _)) = proof {
+                    kept.push((format!("{label}.inv.btor"), text.clone()));
+                }
+            }
+            McResult::Unknown(_) => {
+                if let Some(cex) = cex
+                    && let Some(exact) = cex.bitblast(wts, wsym, &bb).replay(&ts, m.id)
+                {
+                    let cex = bb.restore_cex(&exact);
+                    let named = NamedCex::new(&m.prop.name, &cex, wsym);
+                    kept.push((format!("{label}.cex.ron"), ron::to_string(&named)?));
+                    write_witness(rp, &label, btor, wsym, cex)?;
+                    m.prop.res = McResult::SAT(exact.len() - 1);
+                } else if let Some((text, cert)) = proof
+                    && check_proof(wts, wsym, m.id, cert)
+                {
+                    kept.push((format!("{label}.inv.btor"), text.clone()));
+                    m.prop.res = McResult::UNSAT;
+                } else {
+                    continue;
+                }
+                m.cached = true;
+                reused += 1;
+            }
+        }
+    }
+    let new_dir = rp.path("reuse.new");
+    recreate_dir(&new_dir)?;
+    for (file, text) in kept {
+        fs::write(new_dir.join(file), text)?;
+    }
+    fs::remove_dir_all(&dir)?;
+    fs::rename(&new_dir, &dir)?;
+    Ok(reused)
+}
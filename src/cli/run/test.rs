@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use reuse::NamedCex;
+
+    #[test]
+    fn junit_escapes_xml_special_chars() {
+        assert_eq!(junit::escape("top.p_ok[0]"), "top.p_ok[0]");
+        assert_eq!(
+            junit::escape("a<b && c>\"d\" 'e'"),
+            "a&lt;b &amp;&amp; c&gt;&quot;d&quot; &apos;e&apos;"
+        );
+        assert_eq!(junit::escape("&amp;"), "&amp;amp;");
+    }
+
+    /// A 2-bit latch `x` loading the input `i`, the bad `p_x3` is `x == 3`.
+    const LOAD_BTOR: &str = "\
+1 sort bitvec 2
+2 sort bitvec 1
+3 input 1 i
+4 state 1 x
+5 zero 1
+6 init 1 4 5
+7 next 1 4 3
+8 ones 1
+9 eq 2 4 8
+10 bad 9 p_x3
+";
+
+    #[test]
+    fn named_cex_replays_by_signal_name() {
+        let file = tempfile::Builder::new().suffix(".btor").tempfile().unwrap();
+        fs::write(file.path(), LOAD_BTOR).unwrap();
+        let (wts, wsym) = BtorFrontend::new(Btor::from_file(file.path())).wts();
+        let (ts, bb) = wts.bitblast_to_ts();
+        let replay = |cex: &str| {
+            let cex: NamedCex = ron::from_str(cex).unwrap();
+            cex.bitblast(&wts, &wsym, &bb).replay(&ts, 0)
+        };
+        let exact = replay(r#"(prop: "p_x3", init: [("x", "00")], input: [[("i", "11")], []])"#);
+        assert_eq!(exact.map(|c| c.len()), Some(2));
+        assert!(replay(r#"(prop: "p_x3", init: [], input: [[("i", "01")], []])"#).is_none());
+        // a signal that changed its width is left free
+        assert!(replay(r#"(prop: "p_x3", init: [], input: [[("i", "1")], []])"#).is_some());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::cli::run::{McStatus, PropMcState, Run};
+use crate::cli::run::{McStatus, PropMcState, Run, RunUi, RunUpdates};
 use rIC3::{
     McResult,
     ui::{TerminalInputGuard, restore_terminal},
@@ -9,7 +9,7 @@ use ratatui::{
     crossterm::{style::Stylize, terminal},
     layout::Constraint,
     style::{Color, Style},
-    widgets::{Cell, Row, Table},
+    widgets::{Cell, Paragraph, Row, Table, Widget},
 };
 use std::{io, io::Write, thread::sleep, time::Duration};
 
@@ -26,6 +26,13 @@ const DEFAULT_PLAIN_WIDTH: usize = 80;
 impl PropMcState {
     fn status_columns(&self) -> (String, String) {
         match self.prop.res {
+            McResult::UNSAT if self.cover => (
+                format!("{:<STATE_WIDTH$}", "Unreached")
+                    .yellow()
+                    .bold()
+                    .to_string(),
+                format!("{:<BOUND_WIDTH$}", "-").to_string(),
+            ),
             McResult::UNSAT => (
                 format!("{:<STATE_WIDTH$}", "Proved")
                     .green()
@@ -33,6 +40,20 @@ impl PropMcState {
                     .to_string(),
                 format!("{:<BOUND_WIDTH$}", "-").to_string(),
             ),
+            McResult::SAT(b) if self.cover => (
+                format!("{:<STATE_WIDTH$}", "Covered")
+                    .green()
+                    .bold()
+                    .to_string(),
+                format!("{:<BOUND_WIDTH$}", b).to_string(),
+            ),
+            McResult::SAT(b) if self.abstracted => (
+                format!("{:<STATE_WIDTH$}", "Violated?")
+                    .yellow()
+                    .bold()
+                    .to_string(),
+                format!("{:<BOUND_WIDTH$}", b).to_string(),
+            ),
             McResult::SAT(b) => (
                 format!("{:<STATE_WIDTH$}", "Violated")
                     .red()
@@ -57,6 +78,10 @@ impl PropMcState {
                         .to_string(),
                     format!("{:<BOUND_WIDTH$}", b).blue().to_string(),
                 ),
+                McStatus::Timeout => (
+                    format!("{:<STATE_WIDTH$}", "Timeout").yellow().to_string(),
+                    format!("{:<BOUND_WIDTH$}", b).blue().to_string(),
+                ),
             },
             McResult::Unknown(None) => match self.state {
                 McStatus::Solving => (
@@ -75,6 +100,10 @@ impl PropMcState {
                         .to_string(),
                     format!("{:<BOUND_WIDTH$}", "-").to_string(),
                 ),
+                McStatus::Timeout => (
+                    format!("{:<STATE_WIDTH$}", "Timeout").yellow().to_string(),
+                    format!("{:<BOUND_WIDTH$}", "-").to_string(),
+                ),
             },
         }
     }
@@ -86,9 +115,7 @@ impl PropMcState {
         let property = truncate(&self.prop.name, property_width);
         format!(
             "{} {} {} {} {}",
-            format!("{:<ID_WIDTH$}", format!("p{}", self.id))
-                .magenta()
-                .bold(),
+            format!("{:<ID_WIDTH$}", self.label()).magenta().bold(),
             format!("{property:<property_width$}").white(),
             state,
             bound,
@@ -143,7 +170,8 @@ impl Run {
 
     pub(crate) fn run_plain(&mut self) -> anyhow::Result<()> {
         let line_width = plain_line_width();
-        println!("{}", header_message(line_width));
+        let section = self.mc.first().map_or("Property", PropMcState::section);
+        println!("{}", header_message(line_width, section));
         for prop in &self.mc {
             println!("{}", prop.plain_message(line_width));
         }
@@ -163,9 +191,107 @@ impl Run {
     }
 }
 
+/// Display of `ric3 run --watch`: a live table that stays up across reloads,
+/// with the watch events printed above it.
+pub(crate) enum WatchView {
+    Tui {
+        terminal: RunTerminal,
+        rows: usize,
+        tick: usize,
+    },
+    Plain {
+        line_width: usize,
+    },
+}
+
+impl WatchView {
+    pub(crate) fn new(ui: RunUi, mc: &[PropMcState]) -> anyhow::Result<Self> {
+        let mut view = match ui {
+            RunUi::Tui => Self::Tui {
+                terminal: RunTerminal::new(mc.len() + 2)?,
+                rows: mc.len(),
+                tick: 0,
+            },
+            _ => Self::Plain {
+                line_width: plain_line_width(),
+            },
+        };
+        view.reload(mc)?;
+        Ok(view)
+    }
+
+    pub(crate) fn update(
+        &mut self,
+        mc: &[PropMcState],
+        updates: &RunUpdates,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Tui { terminal, tick, .. } => {
+                *tick += 1;
+                terminal.discard_input();
+                terminal.draw(mc, *tick)
+            }
+            Self::Plain { line_width } => {
+                for &id in &updates.state {
+                    println!("{}", mc[id].plain_message(*line_width));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Shows the table of a reloaded model. The terminal table is
+    /// recreated below the old one when the number of properties changed.
+    pub(crate) fn reload(&mut self, mc: &[PropMcState]) -> anyhow::Result<()> {
+        match self {
+            Self::Tui {
+                terminal,
+                rows,
+                tick,
+            } => {
+                if *rows != mc.len() {
+                    terminal.finish()?;
+                    *terminal = RunTerminal::new(mc.len() + 2)?;
+                    *rows = mc.len();
+                }
+                terminal.draw(mc, *tick)
+            }
+            Self::Plain { line_width } => {
+                println!("{}", header_message(*line_width, "Property"));
+                for prop in mc {
+                    println!("{}", prop.plain_message(*line_width));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn message(&mut self, msg: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Tui { terminal, .. } => {
+                let lines: Vec<_> = msg.lines().collect();
+                terminal.insert_lines(&lines)
+            }
+            Self::Plain { .. } => {
+                println!("{}", msg.bold());
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn finish(&mut self, mc: &[PropMcState]) -> anyhow::Result<()> {
+        if let Self::Tui { terminal, tick, .. } = self {
+            terminal.discard_input();
+            terminal.draw(mc, *tick)?;
+            terminal.finish()?;
+        }
+        Ok(())
+    }
+}
+
 type RunBackend = CrosstermBackend<io::Stdout>;
 
-struct RunTerminal {
+pub(crate) struct RunTerminal {
     terminal: Terminal<RunBackend>,
     input_mode: TerminalInputGuard,
     cursor_hidden: bool,
@@ -197,6 +323,15 @@ impl RunTerminal {
         Ok(())
     }
 
+    /// Prints `lines` above the table, which stays in place.
+    fn insert_lines(&mut self, lines: &[&str]) -> anyhow::Result<()> {
+        let text = lines.join("\n");
+        self.terminal.insert_before(lines.len() as u16, |buf| {
+            Paragraph::new(text).render(buf.area, buf)
+        })?;
+        Ok(())
+    }
+
     fn discard_input(&self) {
         self.input_mode.discard_input();
     }
@@ -241,11 +376,12 @@ fn draw_run_table(f: &mut Frame<'_>, props: &[PropMcState], tick: usize) {
     let prop_width = term_width.saturating_sub(RESERVED_NON_PROP_WIDTH).max(1);
     let mut table_area = size;
     table_area.height = table_area.height.saturating_sub(1);
+    let section = props.first().map_or("Property", PropMcState::section);
 
     let header = Row::new(vec![
         Cell::from(""),
         Cell::from("ID"),
-        Cell::from(truncate("Property", prop_width)),
+        Cell::from(truncate(section, prop_width)),
         Cell::from("State"),
         Cell::from("Bound"),
         Cell::from("Time"),
@@ -272,7 +408,7 @@ fn run_table_row(prop: &PropMcState, prop_width: usize, tick: usize) -> Row<'sta
     let total_time = prop.time + prop.start_time.map_or(Duration::ZERO, |t| t.elapsed());
     Row::new(vec![
         progress_cell(prop, tick),
-        Cell::from(format!("p{}", prop.id)).style(Style::default().fg(Color::Magenta).bold()),
+        Cell::from(prop.label()).style(Style::default().fg(Color::Magenta).bold()),
         Cell::from(truncate(&prop.prop.name, prop_width)).style(Style::default().fg(Color::White)),
         state_cell(prop),
         bound_cell(prop),
@@ -282,9 +418,18 @@ fn run_table_row(prop: &PropMcState, prop_width: usize, tick: usize) -> Row<'sta
 
 fn progress_cell(prop: &PropMcState, tick: usize) -> Cell<'static> {
     match prop.prop.res {
+        McResult::UNSAT if prop.cover => {
+            Cell::from("✘").style(Style::default().fg(Color::Yellow).bold())
+        }
+        McResult::SAT(_) if prop.cover => {
+            Cell::from("✔").style(Style::default().fg(Color::Green).bold())
+        }
         McResult::UNSAT => Cell::from("✔").style(Style::default().fg(Color::Green).bold()),
+        McResult::SAT(_) if prop.abstracted => {
+            Cell::from("✘").style(Style::default().fg(Color::Yellow).bold())
+        }
         McResult::SAT(_) => Cell::from("✘").style(Style::default().fg(Color::Red).bold()),
-        McResult::Unknown(_) if prop.state == McStatus::Pause => {
+        McResult::Unknown(_) if matches!(prop.state, McStatus::Pause | McStatus::Timeout) => {
             Cell::from("⏹").style(Style::default().fg(Color::Yellow).bold())
         }
         McResult::Unknown(_) => {
@@ -297,12 +442,22 @@ fn progress_cell(prop: &PropMcState, tick: usize) -> Cell<'static> {
 
 fn state_cell(prop: &PropMcState) -> Cell<'static> {
     match prop.prop.res {
+        McResult::UNSAT if prop.cover => {
+            Cell::from("Unreached").style(Style::default().fg(Color::Yellow).bold())
+        }
+        McResult::SAT(_) if prop.cover => {
+            Cell::from("Covered").style(Style::default().fg(Color::Green).bold())
+        }
         McResult::UNSAT => Cell::from("Proved").style(Style::default().fg(Color::Green).bold()),
+        McResult::SAT(_) if prop.abstracted => {
+            Cell::from("Violated?").style(Style::default().fg(Color::Yellow).bold())
+        }
         McResult::SAT(_) => Cell::from("Violated").style(Style::default().fg(Color::Red).bold()),
         McResult::Unknown(_) => match prop.state {
             McStatus::Solving => Cell::from("Solving").style(Style::default().fg(Color::Yellow)),
             McStatus::Wait => Cell::from("Waiting").style(Style::default().fg(Color::DarkGray)),
             McStatus::Pause => Cell::from("Paused").style(Style::default().fg(Color::DarkGray)),
+            McStatus::Timeout => Cell::from("Timeout").style(Style::default().fg(Color::Yellow)),
         },
     }
 }
@@ -315,7 +470,7 @@ fn bound_cell(prop: &PropMcState) -> Cell<'static> {
             McStatus::Solving => {
                 Cell::from(b.to_string()).style(Style::default().fg(Color::Cyan).bold())
             }
-            McStatus::Wait | McStatus::Pause => {
+            McStatus::Wait | McStatus::Pause | McStatus::Timeout => {
                 Cell::from(b.to_string()).style(Style::default().fg(Color::Blue))
             }
         },
@@ -323,9 +478,9 @@ fn bound_cell(prop: &PropMcState) -> Cell<'static> {
     }
 }
 
-fn header_message(line_width: usize) -> String {
+fn header_message(line_width: usize, section: &str) -> String {
     let property_width = property_width(line_width);
-    let property = truncate("Property", property_width);
+    let property = truncate(section, property_width);
     format!(
         "{} {} {} {} {}",
         format!("{:<ID_WIDTH$}", "ID").bold(),
@@ -336,6 +491,45 @@ fn header_message(line_width: usize) -> String {
     )
 }
 
+/// Prints the number of proved, violated and unknown properties and the
+/// total time of each task.
+pub(crate) fn print_task_summary(tasks: &[(String, Vec<PropMcState>)]) {
+    let task_width = tasks
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!(
+        "{} {} {} {} {}",
+        format!("{:<task_width$}", "Task").bold(),
+        format!("{:<STATE_WIDTH$}", "Proved").bold(),
+        format!("{:<STATE_WIDTH$}", "Violated").bold(),
+        format!("{:<STATE_WIDTH$}", "Unknown").bold(),
+        format!("{:<TIME_WIDTH$}", "Time").bold(),
+    );
+    for (name, mc) in tasks {
+        let proved = mc.iter().filter(|m| m.prop.res.is_unsat()).count();
+        let violated = mc.iter().filter(|m| m.prop.res.is_sat()).count();
+        let unknown = mc.len() - proved - violated;
+        let time: Duration = mc.iter().map(|m| m.time).sum();
+        let violated_col = format!("{violated:<STATE_WIDTH$}");
+        let violated_col = if violated > 0 {
+            violated_col.red().bold()
+        } else {
+            violated_col.stylize()
+        };
+        println!(
+            "{} {} {} {} {}",
+            format!("{name:<task_width$}").magenta().bold(),
+            format!("{proved:<STATE_WIDTH$}").green(),
+            violated_col,
+            format!("{unknown:<STATE_WIDTH$}").yellow(),
+            format!("{:<TIME_WIDTH$}", format_time(time)),
+        );
+    }
+}
+
 fn property_width(line_width: usize) -> usize {
     line_width
         .saturating_sub(ID_WIDTH + STATE_WIDTH + BOUND_WIDTH + TIME_WIDTH + COLUMN_GAPS)
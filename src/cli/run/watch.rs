@@ -0,0 +1,247 @@
+use super::{
+    McStatus, PropMcState, Run, RunConfig, cached_mc, load_dut, read_model, reuse, ui::WatchView,
+};
+use crate::cli::{
+    FormalConfig, Ric3Config,
+    rproj::{DutHash, Ric3Proj},
+    yosys::Yosys,
+};
+use anyhow::bail;
+use btor::Btor;
+use giputils::file::remove_if_exists;
+use rIC3::{
+    frontend::btor::BtorFrontend,
+    utils::{EngineCtrl, install_interrupt_handler, interrupt_received},
+    wltransys::symbol::WlTsSymbol,
+};
+use std::{
+    fs,
+    path::Path,
+    sync::Arc,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Helper lemmas, cover points and full-model cex checks are not run in watch
+/// mode, so a DUT elaborated into `dut_dir` that needs them is rejected.
+fn check_watchable(ric3_cfg: &Ric3Config, dut_dir: &Path) -> anyhow::Result<()> {
+    if let Some(formal) = ric3_cfg.formal.as_ref() {
+        if formal.invariants.is_some() {
+            bail!("--watch does not support [formal] invariants");
+        }
+        if formal.check_full_cex {
+            bail!("--watch does not support [formal] check_full_cex");
+        }
+    }
+    let cover = dut_dir.join("dut_cover.btor");
+    if cover.exists() && !Btor::from_file(cover).bad.is_empty() {
+        bail!("--watch does not support cover statements");
+    }
+    Ok(())
+}
+
+fn load_config(task: Option<&str>) -> anyhow::Result<Ric3Config> {
+    let ric3_cfg = Ric3Config::from_file("ric3.toml")?;
+    match task {
+        Some(name) => ric3_cfg.task(name),
+        None => Ok(ric3_cfg),
+    }
+}
+
+/// `ric3 run --watch`: checks the properties and then re-verifies on every
+/// change of the DUT sources until Ctrl-C, with the table kept up.
+pub(crate) fn watch(
+    task: Option<&str>,
+    mut ric3_proj: Ric3Proj,
+    cfg: RunConfig,
+) -> anyhow::Result<()> {
+    // Ctrl-C also has to end the watch while no engine is running.
+    install_interrupt_handler(Arc::new(EngineCtrl::new()));
+    let mut ric3_cfg = load_config(task)?;
+    let mut dut_hash = ric3_cfg.dut_hash()?;
+    let elaborated = load_dut(&ric3_cfg, &mut ric3_proj)?;
+    check_watchable(&ric3_cfg, &ric3_proj.path("dut"))?;
+    let (btor, wts, wsym) = read_model(&ric3_cfg, &ric3_proj)?;
+    let mut fingerprints = wts.cone_fingerprints();
+    let mut mc = cached_mc(&ric3_cfg, &ric3_proj, &wts, &wsym)?;
+    if elaborated {
+        reuse::recheck(&ric3_proj, &btor, &wts, &wsym, &mut mc)?;
+    }
+    let ui = cfg.ui.resolve();
+    let formal = ric3_cfg.formal.as_ref();
+    let mut run = Run::new(btor, mc, ric3_proj, wsym, cfg, formal)?;
+    run.launch_nexus()?;
+    let mut view = WatchView::new(ui, &run.mc)?;
+    let mut last_poll = Instant::now();
+    while !interrupt_received() {
+        let updates = run.process_updates()?;
+        if updates.finished {
+            run.cache_res()?;
+            if !interrupt_received() {
+                run.record_history(&ric3_cfg)?;
+            }
+        }
+        view.update(&run.mc, &updates)?;
+        if last_poll.elapsed() >= POLL_INTERVAL {
+            last_poll = Instant::now();
+            // a source may be missing for a moment while an editor saves it
+            // a failed elaboration keeps the old hash, so it is retried
+            if let Ok(hash) = ric3_cfg.dut_hash()
+                && hash != dut_hash
+            {
+                view.message("DUT sources changed, re-elaborating")?;
+                match run.reload(task, &mut fingerprints) {
+                    Ok((new_cfg, new_hash, restarted)) => {
+                        dut_hash = new_hash;
+                        ric3_cfg = new_cfg;
+                        view.reload(&run.mc)?;
+                        view.message(&format!("restarted {restarted} properties"))?;
+                    }
+                    Err(err) => view.message(&elaboration_error(&err))?,
+                }
+            }
+        }
+        sleep(Duration::from_millis(100));
+    }
+    run.abort_nexus()?;
+    view.finish(&run.mc)?;
+    run.cache_res()
+}
+
+/// The first line of `err` and the error lines of the Yosys log.
+fn elaboration_error(err: &anyhow::Error) -> String {
+    let msg = err.to_string();
+    let mut lines = msg.lines();
+    let mut res = vec![format!(
+        "elaboration failed: {}",
+        lines.next().unwrap_or_default()
+    )];
+    res.extend(lines.filter(|l| l.starts_with("ERROR")).map(str::to_string));
+    res.join("\n")
+}
+
+impl Run {
+    fn cache_res(&self) -> anyhow::Result<()> {
+        let res: Vec<_> = self.mc.iter().map(|l| l.prop.clone()).collect();
+        self.ric3_proj.cache_res(res)
+    }
+
+    /// Stops the running PolyNexus task and drops its final results, the
+    /// properties it was solving are left waiting.
+    fn abort_nexus(&mut self) -> anyhow::Result<()> {
+        let Some(task) = self.nexus_task.take() else {
+            return Ok(());
+        };
+        task.ctrl.terminate();
+        task.join
+            .join()
+            .map_err(|_| anyhow::anyhow!("PolyNexus worker thread panicked"))?;
+        for m in self.mc.iter_mut() {
+            if m.state == McStatus::Solving {
+                m.state = McStatus::Wait;
+                if let Some(t) = m.start_time.take() {
+                    m.time += t.elapsed();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-elaborates the DUT aside, swaps it in and relaunches PolyNexus; a
+    /// failed elaboration leaves the current DUT in place. A
+    /// property keeps its state when its name and cone fingerprint at its id
+    /// are unchanged; a proof also needs all properties before it unchanged,
+    /// as PolyNexus assumes those. Returns the reloaded `ric3.toml`, the hash
+    /// of the new DUT and the number of restarted properties.
+    fn reload(
+        &mut self,
+        task: Option<&str>,
+        fingerprints: &mut Vec<u64>,
+    ) -> anyhow::Result<(Ric3Config, DutHash, usize)> {
+        let ric3_cfg = load_config(task)?;
+        let dut_hash = ric3_cfg.dut_hash()?;
+        let new_dut = self.ric3_proj.path("dut.new");
+        let elaborated = Yosys::generate_btor(&ric3_cfg, &new_dut)
+            .and_then(|_| check_watchable(&ric3_cfg, &new_dut));
+        if let Err(err) = elaborated {
+            remove_if_exists(&new_dut)?;
+            return Err(err);
+        }
+        let dut = self.ric3_proj.path("dut");
+        remove_if_exists(&dut)?;
+        // the results and the full model of the old DUT must not be taken
+        // for the new one, even if the reload stops halfway
+        remove_if_exists(self.ric3_proj.path("res/res.ron"))?;
+        remove_if_exists(self.ric3_proj.path("full"))?;
+        fs::rename(&new_dut, &dut)?;
+        self.ric3_proj.cache_dut(&dut_hash)?;
+        let (btor, wts, wsym) = read_model(&ric3_cfg, &self.ric3_proj)?;
+        let new_fingerprints = wts.cone_fingerprints();
+        self.abort_nexus()?;
+        let mut mc = PropMcState::default_from_wts(&wts, &wsym);
+        let abstracted = ric3_cfg
+            .formal
+            .as_ref()
+            .is_some_and(FormalConfig::is_abstract);
+        let mut prefix_kept = true;
+        let mut restarted = Vec::new();
+        for (id, m) in mc.iter_mut().enumerate() {
+            m.abstracted = abstracted;
+            let kept = self.mc.get(id).is_some_and(|old| {
+                old.prop.name == m.prop.name && fingerprints[id] == new_fingerprints[id]
+            });
+            let kept = kept && (prefix_kept || !self.mc[id].prop.res.is_unsat());
+            prefix_kept &= kept;
+            if kept {
+                let old = &self.mc[id];
+                m.prop = old.prop.clone();
+                m.state = old.state;
+                m.time = old.time;
+                m.cached = old.cached;
+            } else {
+                restarted.push(id);
+                for ext in ["wit", "vcd"] {
+                    remove_if_exists(self.ric3_proj.path(format!("res/{}.{ext}", m.label())))?;
+                }
+            }
+        }
+        for old in self.mc.iter().skip(mc.len()) {
+            for ext in ["wit", "vcd"] {
+                remove_if_exists(self.ric3_proj.path(format!("res/{}.{ext}", old.label())))?;
+            }
+        }
+        // changed properties may still be settled by their kept results
+        reuse::recheck(&self.ric3_proj, &btor, &wts, &wsym, &mut mc)?;
+        let restarted = restarted
+            .into_iter()
+            .filter(|&id| mc[id].prop.res.is_unknown())
+            .count();
+        self.swap_model(&ric3_cfg, btor, wsym, mc)?;
+        *fingerprints = new_fingerprints;
+        self.cache_res()?;
+        self.launch_nexus()?;
+        Ok((ric3_cfg, dut_hash, restarted))
+    }
+
+    fn swap_model(
+        &mut self,
+        ric3_cfg: &Ric3Config,
+        btor: Btor,
+        wsym: WlTsSymbol,
+        mc: Vec<PropMcState>,
+    ) -> anyhow::Result<()> {
+        let (worker_cfgs, prop_cfgs) = match ric3_cfg.formal.as_ref() {
+            Some(formal) => (formal.worker_cfgs()?, formal.prop_cfgs(&wsym.prop)?),
+            None => Default::default(),
+        };
+        self.btorfe = BtorFrontend::new(btor.clone());
+        self.btor = btor;
+        self.wsym = wsym;
+        self.mc = mc;
+        self.worker_cfgs = worker_cfgs;
+        self.prop_cfgs = prop_cfgs;
+        Ok(())
+    }
+}
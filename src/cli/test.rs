@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use history::PropRecord;
+    use rIC3::McResult;
+
+    /// A ric3.toml of the DUT `top` with `extra` appended.
+    fn parse(extra: &str) -> Ric3Config {
+        let dut = "[dut]\ntop = \"top\"\nfiles = [\"top.sv\"]\n";
+        toml::from_str(&format!("{dut}{extra}")).unwrap()
+    }
+
+    #[test]
+    fn formal_property_first_match_wins() {
+        let cfg = parse(
+            r#"
+[[formal.property]]
+name = "p_ok"
+priority = 2
+
+[[formal.property]]
+name = "p_fifo_.*"
+engine = "bmc --end 20"
+time_limit = 60
+
+[[formal.property]]
+name = "p_.*"
+priority = 1
+"#,
+        );
+        let formal = cfg.formal.unwrap();
+        let props = ["p_ok", "p_fifo_full", "p_other", "q"].map(String::from);
+        let cfgs = formal.prop_cfgs(&props).unwrap();
+        assert_eq!(cfgs.len(), 3);
+        assert_eq!(cfgs[&0].priority, 2);
+        assert!(cfgs[&0].workers.is_none());
+        assert_eq!(cfgs[&1].time_limit, Some(60));
+        assert_eq!(cfgs[&1].workers.as_ref().map(|w| w.len()), Some(1));
+        assert_eq!(cfgs[&2].priority, 1);
+        assert!(!cfgs.contains_key(&3));
+    }
+
+    #[test]
+    fn formal_property_rejects_invalid_settings() {
+        for property in [
+            "name = \"p\"\nengine = \"ic3\"\npreset = \"bl_default\"",
+            "name = \"p\"\nengine = \"portfolio\"",
+            "name = \"p_(\"",
+        ] {
+            let cfg = parse(&format!("[[formal.property]]\n{property}\n"));
+            assert!(cfg.formal.unwrap().prop_cfgs(&[]).is_err());
+        }
+    }
+
+    fn reset_sequence(cfg: &Ric3Config) -> &ResetSequence {
+        match &cfg.dut.reset {
+            Some(Reset::Sequence(seq)) => seq,
+            _ => panic!("not a reset sequence"),
+        }
+    }
+
+    #[test]
+    fn dut_reset_signal() {
+        let cfg = parse("reset = \"!rst_n\"\n");
+        assert_eq!(cfg.reset_cycles(), 1);
+        assert_eq!(
+            cfg.reset_seq(),
+            vec![("rst_n".to_string(), "0,1".to_string())]
+        );
+        assert_eq!(parse("").reset_cycles(), 0);
+        assert!(parse("").reset_seq().is_empty());
+    }
+
+    #[test]
+    fn dut_reset_sequence() {
+        let cfg = parse(
+            r#"
+[dut.reset]
+signals = ["rst", "!prst_n"]
+cycles = 2
+hold = { prst_n = 3 }
+values = { valid = "1'b0", data = "8'h00" }
+"#,
+        );
+        reset_sequence(&cfg).validate().unwrap();
+        assert_eq!(cfg.reset_cycles(), 3);
+        let seq: Vec<_> = cfg
+            .reset_seq()
+            .into_iter()
+            .map(|(n, p)| format!("{n}={p}"))
+            .collect();
+        assert_eq!(
+            seq,
+            [
+                "rst=1,1,0",
+                "prst_n=0,0,0,1",
+                "data=8'h00,8'h00,8'h00",
+                "valid=1'b0,1'b0,1'b0",
+            ]
+        );
+    }
+
+    #[test]
+    fn dut_reset_rejects_invalid_sequence() {
+        for reset in [
+            "signals = []",
+            "signals = [\"rst\"]\ncycles = 0",
+            "signals = [\"rst\"]\nhold = { rst = 0 }",
+            "signals = [\"rst\"]\nhold = { clk = 2 }",
+            "signals = [\"!rst_n\"]\nvalues = { rst_n = \"1\" }",
+        ] {
+            let cfg = parse(&format!("[dut.reset]\n{reset}\n"));
+            assert!(reset_sequence(&cfg).validate().is_err());
+        }
+    }
+
+    #[test]
+    fn tasks_override_dut() {
+        let cfg = parse(
+            r#"defines = { SIM = "0" }
+parameters = { DEPTH = "4" }
+
+[[task]]
+name = "wide"
+parameters = { WIDTH = "64" }
+
+[[task]]
+name = "fast"
+top = "top_fast"
+defines = { SIM = "1" }
+"#,
+        );
+        let wide = cfg.task("wide").unwrap();
+        assert_eq!(wide.dut.top, "top");
+        assert_eq!(wide.dut.parameters.len(), 2);
+        assert_eq!(wide.dut.parameters["WIDTH"], "64");
+        let fast = cfg.task("fast").unwrap();
+        assert_eq!(fast.dut.top, "top_fast");
+        assert_eq!(fast.dut.defines["SIM"], "1");
+        assert_eq!(cfg.dut.defines["SIM"], "0");
+        assert!(cfg.task("slow").is_err());
+    }
+
+    #[test]
+    fn dut_settings_cover_elaboration() {
+        let base = parse("defines = { A = \"1\", B = \"2\" }\n");
+        let same = parse("defines = { B = \"2\", A = \"1\" }\n");
+        assert_eq!(base.dut_settings(), same.dut_settings());
+        for extra in [
+            "defines = { A = \"1\", B = \"3\" }\n",
+            "defines = { A = \"1\", B = \"2\" }\nparameters = { W = \"8\" }\n",
+            "defines = { A = \"1\", B = \"2\" }\nreset = \"rst\"\n",
+            "defines = { A = \"1\", B = \"2\" }\n[formal]\nblackbox = [\"mem\"]\n",
+            "defines = { A = \"1\", B = \"2\" }\n[formal]\ncutpoints = [\"top.x\"]\n",
+        ] {
+            assert_ne!(base.dut_settings(), parse(extra).dut_settings());
+        }
+        let cfg = parse("\n[[task]]\nname = \"t\"\ndefines = { SIM = \"1\" }\n");
+        assert_ne!(cfg.dut_settings(), cfg.task("t").unwrap().dut_settings());
+    }
+
+    #[test]
+    fn tasks_selection() {
+        let cfg = parse("\n[[task]]\nname = \"b\"\n\n[[task]]\nname = \"a\"\n");
+        assert_eq!(cfg.select_tasks(&[], true).unwrap(), ["b", "a"]);
+        let names = ["a".to_string()];
+        assert_eq!(cfg.select_tasks(&names, false).unwrap(), ["a"]);
+        assert!(cfg.select_tasks(&["c".to_string()], false).is_err());
+        assert!(parse("").select_tasks(&[], true).is_err());
+        assert!(parse("").select_tasks(&[], false).unwrap().is_empty());
+    }
+
+    fn prop_record(res: McResult) -> PropRecord {
+        PropRecord {
+            name: "p".to_string(),
+            res,
+            timeout: false,
+            cached: false,
+            time: 1.0,
+            engine: None,
+        }
+    }
+
+    #[test]
+    fn regressions_between_runs() {
+        use McResult::*;
+        let (proved, violated, unknown) = (UNSAT, SAT(3), Unknown(Some(10)));
+        for (before, now, regressed) in [
+            (proved, unknown, true),
+            (violated, unknown, true),
+            (proved, violated, true),
+            (unknown, violated, true),
+            (violated, SAT(5), false),
+            (violated, proved, false),
+            (unknown, proved, false),
+            (proved, proved, false),
+            (unknown, Unknown(None), false),
+        ] {
+            assert_eq!(
+                prop_record(now).regressed_from(&prop_record(before)),
+                regressed,
+                "{before:?} -> {now:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use clap::Args;
+use rIC3::{
+    Engine, McResult, MpEngine,
+    polynexus::{PolyNexus, PolyNexusConfig},
+    report::{Report, VacuityReport},
+    transys::Transys,
+    utils::{install_interrupt_handler, interrupt_received},
+    wltransys::{WlTransys, symbol::WlTsSymbol},
+};
+use ratatui::crossterm::style::Stylize;
+use tabled::{
+    Table, Tabled,
+    settings::{Format, Modify, Style, object::Rows},
+};
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct VacuityConfig {
+    /// Check that the assumptions are satisfiable, and which of them the
+    /// proof of each proved property depends on
+    #[arg(long = "vacuity")]
+    pub vacuity: bool,
+
+    /// Depth up to which the assumptions are checked satisfiable by BMC
+    #[arg(long = "vacuity-depth", default_value_t = 20)]
+    pub vacuity_depth: usize,
+
+    /// Time limit in seconds of a property re-checked without an assumption
+    #[arg(long = "vacuity-time-limit", default_value_t = 60)]
+    pub vacuity_time_limit: u64,
+}
+
+/// Outcome of the vacuity checks.
+pub(crate) struct Vacuity {
+    pub report: VacuityReport,
+    /// names of the assumptions the proof of each proved property depends
+    /// on, by property id
+    pub deps: Vec<(usize, Vec<String>)>,
+}
+
+/// Names of the assumptions of `wts`, the signal name of a constraint or its
+/// index.
+pub(crate) fn assumption_names(wts: &WlTransys, wsym: &WlTsSymbol) -> Vec<String> {
+    wts.constraint
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            wsym.signal
+                .iter()
+                .find(|(t, _)| *t == c)
+                .and_then(|(_, n)| n.iter().next().cloned())
+                .unwrap_or_else(|| format!("assume{i}"))
+        })
+        .collect()
+}
+
+/// Checks the assumptions `names` of `ts` by BMC, and re-checks the proved
+/// `props` by PolyNexus on `without(i)`, the model without assumption `i`. An
+/// assumption is needed by a proof when the property is then violated, and
+/// marked with `?` when it is not proved again within the time limit. The
+/// bads `lemmas` are re-proved along, so that PolyNexus assumes those that
+/// still hold.
+pub(crate) fn check(
+    cfg: &VacuityConfig,
+    ts: &Transys,
+    names: &[String],
+    props: &[usize],
+    lemmas: &[usize],
+    without: impl Fn(usize) -> Transys,
+    mut nexus: PolyNexusConfig,
+) -> anyhow::Result<Vacuity> {
+    let report = VacuityReport {
+        assumptions: names.len(),
+        depth: cfg.vacuity_depth,
+        conflict: ts.constraint_conflict(cfg.vacuity_depth),
+    };
+    let mut deps: Vec<_> = props.iter().map(|&p| (p, Vec::new())).collect();
+    nexus.prop_time_limit = Some(cfg.vacuity_time_limit);
+    for (i, name) in names.iter().enumerate() {
+        if props.is_empty() || interrupt_received() {
+            break;
+        }
+        let mut ts = without(i);
+        ts.bad = props.iter().chain(lemmas).map(|&p| ts.bad[p]).collect();
+        let mut engine = PolyNexus::new(
+            nexus.clone(),
+            ts,
+            vec![McResult::Unknown(None); props.len() + lemmas.len()],
+        )?;
+        install_interrupt_handler(engine.get_ctrl());
+        let res = MpEngine::check(&mut engine);
+        if interrupt_received() {
+            deps.clear();
+            break;
+        }
+        for ((_, d), r) in deps.iter_mut().zip(res) {
+            match r {
+                McResult::SAT(_) => d.push(name.clone()),
+                McResult::Unknown(_) => d.push(format!("{name}?")),
+                McResult::UNSAT => (),
+            }
+        }
+    }
+    Ok(Vacuity { report, deps })
+}
+
+impl Vacuity {
+    pub(crate) fn print(&self, prop_name: impl Fn(usize) -> String) {
+        #[derive(Tabled)]
+        struct Dependency {
+            #[tabled(rename = "Property")]
+            property: String,
+            #[tabled(rename = "Depends on")]
+            assumptions: String,
+        }
+        println!("{}", "Vacuity".bold());
+        let report = &self.report;
+        if report.assumptions == 0 {
+            println!("No assumptions");
+            return;
+        }
+        match report.conflict {
+            Some(0) => println!(
+                "{}",
+                "Assumptions contradict the initial states, every property holds vacuously".red()
+            ),
+            Some(d) => println!(
+                "{}",
+                format!(
+                    "Assumptions admit no path of depth {d}, every property holds vacuously from there"
+                )
+                .red()
+            ),
+            None => println!(
+                "{}",
+                format!(
+                    "{} assumptions satisfiable up to depth {}",
+                    report.assumptions, report.depth
+                )
+                .green()
+            ),
+        }
+        if self.deps.is_empty() {
+            return;
+        }
+        let rows: Vec<_> = self
+            .deps
+            .iter()
+            .map(|(p, d)| Dependency {
+                property: prop_name(*p),
+                assumptions: if d.is_empty() {
+                    "-".to_string()
+                } else {
+                    d.join(", ")
+                },
+            })
+            .collect();
+        let mut table = Table::new(&rows);
+        table.with(Style::empty()).with(
+            Modify::new(Rows::first()).with(Format::content(|s| s.yellow().bold().to_string())),
+        );
+        println!("{table}");
+    }
+
+    pub(crate) fn fill_report(self, report: &mut Report) {
+        for (p, d) in self.deps {
+            if let Some(prop) = report.properties.iter_mut().find(|r| r.id == p) {
+                prop.assumptions = Some(d);
+            }
+        }
+        report.vacuity = Some(self.report);
+    }
+}
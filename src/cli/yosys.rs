@@ -1,6 +1,6 @@
 use super::Ric3Config;
 use crate::cli::Parse;
-use giputils::file::recreate_dir;
+use giputils::{file::recreate_dir, hash::GHashMap};
 use log::info;
 use std::{
     fs,
@@ -47,8 +47,14 @@ impl Yosys {
         Ok(())
     }
 
+    /// The DUT of `ric3 run`, with its covers in `dut_cover.btor`.
     pub fn generate_btor(cfg: &Ric3Config, p: impl AsRef<Path>) -> anyhow::Result<()> {
-        Self::generate_btor_with_files(cfg, &cfg.dut.files, p, "dut", true)
+        Self::elaborate(cfg, &cfg.dut.files, p, "dut", true, cfg.blackbox(), true)
+    }
+
+    /// The DUT without the `[formal] blackbox` abstraction.
+    pub fn generate_full_btor(cfg: &Ric3Config, p: impl AsRef<Path>) -> anyhow::Result<()> {
+        Self::elaborate(cfg, &cfg.dut.files, p, "dut", true, &[], false)
     }
 
     pub fn generate_btor_with_files(
@@ -57,6 +63,18 @@ impl Yosys {
         p: impl AsRef<Path>,
         stem: &str,
         reset: bool,
+    ) -> anyhow::Result<()> {
+        Self::elaborate(cfg, input_files, p, stem, reset, &[], false)
+    }
+
+    fn elaborate(
+        cfg: &Ric3Config,
+        input_files: &[PathBuf],
+        p: impl AsRef<Path>,
+        stem: &str,
+        reset: bool,
+        blackbox: &[String],
+        covers: bool,
     ) -> anyhow::Result<()> {
         info!("Yosys: parsing SystemVerilog and generating BTOR.");
         let slang = cfg
@@ -90,27 +108,52 @@ impl Yosys {
         for define in define_args(&cfg.dut.defines) {
             read.push_str(&format!(" {define}"));
         }
+        let parameters = sorted(&cfg.dut.parameters);
+        if slang {
+            for (name, value) in parameters.iter() {
+                read.push_str(&format!(" -G {name}={value}"));
+            }
+        }
         for file in files.iter() {
             read.push_str(&format!(" {}", file.display()));
         }
         yosys.add_command(&read);
+        if !slang {
+            for (name, value) in parameters.iter() {
+                yosys.add_command(&format!("chparam -set {name} {value} {}", cfg.dut.top));
+            }
+        }
+        // instances of blackbox modules become cut points with free outputs
+        for module in blackbox {
+            yosys.add_command(&format!("blackbox {module}"));
+        }
+        if !blackbox.is_empty() {
+            yosys.add_command("cutpoint -blackbox");
+        }
         yosys.add_command("setundef -undriven -anyseq");
         yosys.add_command("opt_clean");
         yosys.add_command("memory_nordff");
-        if reset && let Some((reset, pol)) = cfg.reset() {
-            yosys.add_command(&format!(
-                "fminit -seq {} {}",
-                reset,
-                if pol { "1,0" } else { "0,1" }
-            ));
+        if reset {
+            for (signal, seq) in cfg.reset_seq() {
+                yosys.add_command(&format!("fminit -seq {signal} {seq}"));
+            }
+            // assertions and covers are checked from the last reset cycle on,
+            // as with a single-cycle reset
+            let skip = cfg.reset_cycles().saturating_sub(1);
+            if skip > 0 {
+                yosys.add_command(&format!("chformal -assert -cover -skip {skip}"));
+            }
         }
-        yosys.add_command("chformal -cover -remove");
         yosys.add_command("chformal -early");
         yosys.add_command("async2sync");
         yosys.add_command("formalff -clk2ff -ff2anyinit -hierarchy -assume");
         yosys.add_command("dffunmap");
         yosys.add_command("opt -fast");
         yosys.add_command("check");
+        if covers {
+            yosys.add_command("design -save formal");
+        }
+        yosys.add_command("chformal -cover -remove");
         let dp = PathBuf::from("..");
         yosys.add_command(&format!(
             "write_rtlil {}",
@@ -120,6 +163,15 @@ impl Yosys {
             "write_btor {}",
             dp.join(format!("{stem}.btor")).display(),
         ));
+        // covers are kept as the bads of a separate model
+        if covers {
+            yosys.add_command("design -load formal");
+            yosys.add_command("chformal -assert -remove");
+            yosys.add_command(&format!(
+                "write_btor -c {}",
+                dp.join(format!("{stem}_cover.btor")).display(),
+            ));
+        }
         let plugin = if slang {
             vec!["slang".to_string()]
         } else {
@@ -129,10 +181,14 @@ impl Yosys {
     }
 }
 
-fn define_args(defines: &giputils::hash::GHashMap<String, String>) -> Vec<String> {
-    let mut defines = defines.iter().collect::<Vec<_>>();
-    defines.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
-    defines
+fn sorted(map: &GHashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    entries
+}
+
+fn define_args(defines: &GHashMap<String, String>) -> Vec<String> {
+    sorted(defines)
         .into_iter()
         .map(|(name, value)| {
             if value.is_empty() {
@@ -3,11 +3,12 @@ use crate::{
     polynexus::PolyNexusConfig, portfolio::PortfolioConfig, rlive::RliveConfig, wlbmc::WlBMCConfig,
     wlkind::WlKindConfig,
 };
+use anyhow::Context;
 use clap::{ArgAction, Args, Parser};
 use enum_as_inner::EnumAsInner;
 use giputils::hash::GHashMap;
 use serde::{Deserialize, Serialize};
-use std::{iter, time::Instant};
+use std::{fs, iter, path::Path, time::Instant};
 use strum::AsRefStr;
 
 /// Macro to implement Deref and DerefMut for config structs that wrap EngineConfigBase
@@ -102,6 +103,13 @@ pub enum EngineConfig {
 }
 
 impl EngineConfig {
+    pub fn is_mp(&self) -> bool {
+        matches!(
+            self,
+            EngineConfig::MultiProp(_) | EngineConfig::Polynexus(_)
+        )
+    }
+
     pub fn is_wl(&self) -> bool {
         matches!(
             self,
@@ -135,6 +143,54 @@ pub struct PreprocConfig {
     /// scorr time limit in seconds
     #[arg(long = "scorr-tl", default_value_t = 200)]
     pub scorr_tl: u64,
+
+    /// k-step signal correspondence over latches and internal signals
+    #[arg(long = "kscorr", action = ArgAction::Set, default_value_t = false)]
+    pub kscorr: bool,
+
+    /// kscorr induction depth
+    #[arg(
+        long = "kscorr-k",
+        default_value_t = 2,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub kscorr_k: usize,
+
+    /// kscorr time limit in seconds
+    #[arg(long = "kscorr-tl", default_value_t = 200)]
+    pub kscorr_tl: u64,
+
+    /// ternary simulation based constant latch detection
+    #[arg(long = "tsim", action = ArgAction::Set, default_value_t = false)]
+    pub tsim: bool,
+
+    /// maximum transient prefix in steps of tsim
+    #[arg(long = "tsim-prefix", default_value_t = 8)]
+    pub tsim_prefix: usize,
+
+    /// target enlargement
+    #[arg(long = "enlarge", action = ArgAction::Set, default_value_t = false)]
+    pub enlarge: bool,
+
+    /// target enlargement steps
+    #[arg(long = "enlarge-k", default_value_t = 2)]
+    pub enlarge_k: usize,
+
+    /// input reparameterization
+    #[arg(long = "reparam", action = ArgAction::Set, default_value_t = false)]
+    pub reparam: bool,
+
+    /// maximum number of cut points of a reparameterized input component
+    #[arg(long = "reparam-cut", default_value_t = 8)]
+    pub reparam_cut: usize,
+
+    /// minimum-register retiming
+    #[arg(long = "retime", action = ArgAction::Set, default_value_t = false)]
+    pub retime: bool,
+
+    /// retime time limit in seconds
+    #[arg(long = "retime-tl", default_value_t = 100)]
+    pub retime_tl: u64,
 }
 
 impl Default for PreprocConfig {
@@ -146,13 +202,38 @@ impl Default for PreprocConfig {
             frts_tl: 1000,
             scorr: true,
             scorr_tl: 200,
+            kscorr: false,
+            kscorr_k: 2,
+            kscorr_tl: 200,
+            tsim: false,
+            tsim_prefix: 8,
+            enlarge: false,
+            enlarge_k: 2,
+            reparam: false,
+            reparam_cut: 8,
+            retime: false,
+            retime_tl: 100,
         }
     }
 }
 
-#[derive(Default)]
+/// Named worker command lines of a portfolio, one TOML section per preset:
+///
+/// ```toml
+/// [bl_default]
+/// ic3 = "ic3"
+/// bmc = "bmc --step 10"
+/// ```
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct WorkerConfigs {
-    configs: GHashMap<String, String>,
+    configs: GHashMap<String, WorkerConfig>,
+}
+
+/// A worker, either as a command line or as an engine config built in code.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum WorkerConfig {
+    Args(String),
+    Engine(Box<EngineConfig>),
 }
 
 impl WorkerConfigs {
@@ -160,39 +241,117 @@ impl WorkerConfigs {
         Self::default()
     }
 
-    pub fn from_toml(toml: &str, config: &str) -> Self {
-        let mut portfolio_config: GHashMap<String, GHashMap<String, String>> =
-            toml::from_str(toml).unwrap();
-        let Some(configs) = portfolio_config.remove(config) else {
-            panic!("unknown worker config `{config}`");
-        };
+    /// Worker configs from worker names to engine arguments, all of which
+    /// must parse.
+    pub fn from_map(configs: GHashMap<String, String>) -> anyhow::Result<Self> {
+        if configs.is_empty() {
+            anyhow::bail!("worker config has no workers");
+        }
+        for (name, args) in configs.iter() {
+            let argv = iter::once("").chain(args.split_whitespace());
+            if let Err(err) = EngineConfig::try_parse_from(argv) {
+                anyhow::bail!("invalid worker `{name}` = \"{args}\":\n{err}");
+            }
+        }
+        let configs = configs
+            .into_iter()
+            .map(|(name, args)| (name, WorkerConfig::Args(args)))
+            .collect();
+        Ok(Self { configs })
+    }
+
+    /// A single worker running `cfg` as it is, including its random seed.
+    pub fn from_engine(name: &str, cfg: EngineConfig) -> Self {
+        let configs =
+            GHashMap::from_iter([(name.to_string(), WorkerConfig::Engine(Box::new(cfg)))]);
         Self { configs }
     }
 
+    /// The preset `config` of `toml`.
+    pub fn from_toml(toml: &str, config: &str) -> anyhow::Result<Self> {
+        let mut presets: GHashMap<String, GHashMap<String, String>> = toml::from_str(toml)?;
+        let Some(configs) = presets.remove(config) else {
+            let mut known: Vec<_> = presets.into_keys().collect();
+            known.sort();
+            anyhow::bail!(
+                "unknown worker config `{config}`, available: {}",
+                known.join(", ")
+            );
+        };
+        Self::from_map(configs).with_context(|| format!("invalid worker config `{config}`"))
+    }
+
+    /// Loads `config`, either a preset of the builtin `toml` or a path to a
+    /// user TOML file in the same format. `preset` selects a preset of a user
+    /// file, which may be left out if the file has a single preset.
+    pub fn load(toml: &str, config: &str, preset: Option<&str>) -> anyhow::Result<Self> {
+        let path = Path::new(config);
+        if !path.is_file() {
+            if let Some(preset) = preset {
+                anyhow::bail!(
+                    "preset `{preset}` is given, but worker config `{config}` is not a file"
+                );
+            }
+            return Self::from_toml(toml, config);
+        }
+        let user = fs::read_to_string(path)
+            .with_context(|| format!("failed to read worker config file: {}", path.display()))?;
+        let presets: GHashMap<String, GHashMap<String, String>> = toml::from_str(&user)
+            .with_context(|| format!("failed to parse worker config file: {}", path.display()))?;
+        let preset = match preset {
+            Some(preset) => preset,
+            None if presets.len() == 1 => presets.keys().next().unwrap().as_str(),
+            None => {
+                let mut known: Vec<_> = presets.keys().map(|k| k.as_str()).collect();
+                known.sort();
+                anyhow::bail!(
+                    "{} has the presets {}, select one with --preset",
+                    path.display(),
+                    known.join(", ")
+                );
+            }
+        };
+        Self::from_toml(&user, preset).with_context(|| format!("in {}", path.display()))
+    }
+
     pub fn len(&self) -> usize {
         self.configs.len()
     }
 
+    /// The command line of each worker, a worker built from an engine config
+    /// has the name of its engine.
     pub fn iter_args(&self, auto_rseed: bool) -> impl Iterator<Item = (String, String)> + '_ {
         self.configs
             .iter()
             .enumerate()
-            .map(move |(idx, (name, args))| {
-                let args = if auto_rseed {
-                    format!("{args} --rseed {}", idx + 1)
-                } else {
-                    args.clone()
+            .map(move |(idx, (name, cfg))| {
+                let args = match cfg {
+                    WorkerConfig::Args(args) if auto_rseed => {
+                        format!("{args} --rseed {}", idx + 1)
+                    }
+                    WorkerConfig::Args(args) => args.clone(),
+                    WorkerConfig::Engine(cfg) => AsRef::<str>::as_ref(&**cfg).to_lowercase(),
                 };
                 (name.clone(), args)
             })
     }
 
+    /// The engine config of each worker. `auto_rseed` gives each worker of a
+    /// command line its own random seed, a worker built from an engine config
+    /// keeps its seed.
     pub fn iter(&self, auto_rseed: bool) -> impl Iterator<Item = (String, EngineConfig)> + '_ {
-        self.iter_args(auto_rseed).map(|(name, args)| {
-            let argv = iter::once("").chain(args.split_whitespace());
-            let cfg = EngineConfig::try_parse_from(argv)
-                .unwrap_or_else(|err| panic!("invalid worker config `{name}`: {err}"));
-            (name, cfg)
-        })
+        self.iter_args(auto_rseed)
+            .zip(self.configs.values())
+            .map(|((name, args), cfg)| {
+                let cfg = match cfg {
+                    WorkerConfig::Args(_) => {
+                        let argv = iter::once("").chain(args.split_whitespace());
+                        EngineConfig::try_parse_from(argv)
+                            .unwrap_or_else(|err| panic!("invalid worker config `{name}`: {err}"))
+                    }
+                    WorkerConfig::Engine(cfg) => (**cfg).clone(),
+                };
+                (name, cfg)
+            })
     }
 }
@@ -117,6 +117,8 @@ fn aig_symbols(aig: &Aig) -> VarSymbols {
 }
 
 pub struct AigFrontend {
+    /// the model with its outputs moved to bad properties
+    aig: Aig,
     ts: Transys,
     ts_symbols: VarSymbols,
 }
@@ -153,7 +155,11 @@ impl AigFrontend {
         }
         let ts_symbols = aig_symbols(&aig);
         let ts = Transys::from_aig(&aig, true);
-        Self { ts, ts_symbols }
+        Self {
+            aig: oaig,
+            ts,
+            ts_symbols,
+        }
     }
 
     pub fn is_safety(&self) -> bool {
@@ -236,6 +242,12 @@ impl Frontend for AigFrontend {
         }
     }
 
+    fn restrict_props(&self, props: &[usize]) -> Option<Box<dyn Display>> {
+        let mut aig = self.aig.clone();
+        aig.bads = props.iter().map(|&p| self.aig.bads[p]).collect();
+        Some(Box::new(aig))
+    }
+
     fn certify(&mut self, model: &Path, cert: &Path) -> bool {
         certifaiger_check(model, cert)
     }
@@ -3,7 +3,7 @@ mod array;
 use super::Frontend;
 use crate::{
     McBlCertificate, McWlCertificate,
-    transys::{self as bl},
+    transys::{self as bl, certify::BlProof},
     wltransys::{
         WlTransys,
         bitblast::BitblastMap,
@@ -228,6 +228,12 @@ impl Frontend for BtorFrontend {
         (self.wts.clone(), self.symbols.clone())
     }
 
+    fn restrict_props(&self, props: &[usize]) -> Option<Box<dyn Display>> {
+        let mut wts = self.owts.clone();
+        wts.bad = props.iter().map(|&p| self.owts.bad[p].clone()).collect();
+        Some(Box::new(Btor::from(&wts)))
+    }
+
     fn certify(&mut self, model: &Path, cert: &Path) -> bool {
         cerbtora_check(model, cert)
     }
@@ -258,6 +264,18 @@ impl Frontend for BtorFrontend {
 }
 
 impl BtorFrontend {
+    /// Word-level proof of a proof of [`Frontend::ts`], over the signals of
+    /// the BTOR model.
+    pub fn wl_proof(&self, proof: &BlProof) -> WlProof {
+        let mut proof = self
+            .bb_rst
+            .as_ref()
+            .unwrap()
+            .restore_proof(&self.wts, proof);
+        self.tf.inv_trans_proof(&mut proof);
+        proof
+    }
+
     fn wl_safe_certificate(&mut self, mut proof: WlProof) -> Box<dyn Display> {
         self.tf.inv_trans_proof(&mut proof);
         let mut btor = self.owts.clone();
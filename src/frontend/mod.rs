@@ -4,7 +4,7 @@ use crate::{
         aig::{AigFrontend, certifaiger_check},
         btor::{BtorFrontend, cerbtora_check},
     },
-    transys::Transys,
+    transys::{Transys, certify::BlCex},
     wltransys::{WlTransys, symbol::WlTsSymbol},
 };
 use ::aig::Aig;
@@ -29,6 +29,21 @@ pub trait Frontend: Send {
 
     fn bl_certificate(&mut self, cert: McBlCertificate) -> Box<dyn Display>;
 
+    /// Multi-property witness with one block per violated property.
+    fn bl_mp_witness(&mut self, cexs: Vec<BlCex>) -> Box<dyn Display> {
+        let blocks: Vec<_> = cexs
+            .into_iter()
+            .map(|cex| self.bl_certificate(McBlCertificate::SAT(cex)).to_string())
+            .collect();
+        Box::new(blocks.concat())
+    }
+
+    /// The model with only the bad properties `props`, in aiger ascii format
+    /// or in btor2 format, to check a certificate of these properties alone.
+    fn restrict_props(&self, _props: &[usize]) -> Option<Box<dyn Display>> {
+        None
+    }
+
     fn wl_certificate(&mut self, _cert: McWlCertificate) -> Box<dyn Display> {
         panic!("unsupport wl_certificate")
     }
@@ -209,6 +209,15 @@ impl Frames {
         }
     }
 
+    /// Number of lemmas in each frame, the infinite frame last.
+    pub fn lemma_count(&self) -> Vec<usize> {
+        self.frames
+            .iter()
+            .map(|f| f.len())
+            .chain([self.inf.len()])
+            .collect()
+    }
+
     #[inline]
     pub fn statistic(&self, compact: bool) -> String {
         const COMPACT_FRAME_LIMIT: usize = 50;
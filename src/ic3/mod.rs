@@ -4,6 +4,7 @@ use crate::{
     gipsat::{SolverStatistic, TransysSolver},
     ic3::{block::BlockResult, localabs::LocalAbs, predprop::PredProp},
     impl_config_deref,
+    report::{Report, TsStatistic},
     tracer::{Tracer, TracerIf},
     transys::{
         Transys, TransysCtx, TransysIf, certify::Restore, lift::TsLift, unroll::TransysUnroll,
@@ -20,6 +21,7 @@ use logicrs::{Lit, LitOrdVec, LitVec, LitVvec, Var, VarMap, VarSymbols, satif::S
 use proofoblig::{ProofObligation, ProofObligationQueue};
 use rand::{SeedableRng, rngs::StdRng};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::{ops::Deref, sync::Arc, time::Instant};
 use utils::Statistic;
 
@@ -230,6 +232,12 @@ impl IC3 {
 }
 
 impl IC3 {
+    /// Whether the proof assumes the properties other than the checked one.
+    #[inline]
+    pub fn is_local_proof(&self) -> bool {
+        self.cfg.local_proof
+    }
+
     pub fn new(cfg: IC3Config, mut ts: Transys, symbols: VarSymbols) -> Self {
         cfg.validate();
         let ots = ts.clone();
@@ -395,6 +403,40 @@ impl Engine for IC3 {
         info!("{:#?}", self.statistic);
     }
 
+    fn report(&mut self, report: &mut Report) {
+        self.statistic.num_auxiliary_var = self.auxiliary_var.len();
+        report.preproc_ts = Some(TsStatistic::new(self.ts.deref()));
+        let solver = self
+            .solvers
+            .iter()
+            .fold(SolverStatistic::default(), |mut acc, s| {
+                acc += *s.statistic();
+                acc
+            });
+        let stat = &mut report.statistic;
+        stat.insert(
+            "solver".to_string(),
+            json!({
+                "num_solve": solver.num_solve,
+                "num_simplify_subsume": solver.num_simplify_subsume,
+                "num_simplify_self_subsume": solver.num_simplify_self_subsume,
+            }),
+        );
+        let ic3 = &self.statistic;
+        stat.insert(
+            "ic3".to_string(),
+            json!({
+                "time": ic3.time.time().as_secs_f64(),
+                "num_mic": ic3.num_mic,
+                "num_down": ic3.num_down,
+                "num_down_sat": ic3.num_down_sat,
+                "num_auxiliary_var": ic3.num_auxiliary_var,
+            }),
+        );
+        stat.insert("frame".to_string(), json!(self.frame.lemma_count()));
+        stat.insert("obligations".to_string(), json!(self.obligations.count()));
+    }
+
     fn get_ctrl(&self) -> Arc<dyn TerminateCtrl> {
         self.ctrl.clone()
     }
@@ -403,6 +445,7 @@ impl Engine for IC3 {
 impl BlEngine for IC3 {
     fn proof(&mut self) -> BlProof {
         let mut proof = self.ots.clone();
+        self.rst.add_time_witness(&mut proof);
         if let Some(iv) = self.rst.init_var() {
             let piv = proof.add_init_var();
             self.rst.add_restore(iv, piv);
@@ -426,6 +469,9 @@ impl BlEngine for IC3 {
         let invariants = proof.rel.new_or(certifaiger_dnf);
         let bad = proof.rel.new_or(proof.bad);
         proof.bad = LitVec::from(proof.rel.new_or([invariants, bad]));
+        if self.rst.eq_depth() > 1 {
+            return BlProof::k_induction(proof, self.rst.eq_depth());
+        }
         BlProof { proof }
     }
 
@@ -448,16 +494,13 @@ impl BlEngine for IC3 {
             }
             res
         };
-        let iv = self.rst.init_var();
-        res = res.filter_map(|l| {
-            (iv != Some(l.var()))
-                .then(|| self.rst.try_restore(l))
-                .flatten()
-        });
-        for s in res.state.iter_mut() {
-            *s = self.rst.restore_eq_state(s);
+        res = self.rst.restore_cex(&res);
+        if let Some(prop) = self.cfg.prop {
+            res.bad_id = prop;
+        }
+        if let Err(err) = res.exact_state(&self.ots, true, self.rst.enlarge()) {
+            error!("failed to complete the cex: {err}");
         }
-        res.exact_state(&self.ots, true);
         res
     }
 }
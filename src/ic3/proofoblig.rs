@@ -208,6 +208,11 @@ impl ProofObligationQueue {
     pub fn statistic(&self) -> String {
         format!("{:?}", self.num)
     }
+
+    /// Number of obligations at each frame.
+    pub fn count(&self) -> &[usize] {
+        &self.num
+    }
 }
 
 impl IC3 {
@@ -2,6 +2,7 @@ use crate::{
     BlCex, BlEngine, BlProof, Engine, McResult,
     config::{EngineConfig, EngineConfigBase, PreprocConfig},
     impl_config_deref,
+    report::{Report, TsStatistic},
     tracer::{Tracer, TracerIf},
     transys::{Transys, TransysIf, certify::Restore, nodep::NoDepTransys, unroll::TransysUnroll},
     utils::EngineCtrl,
@@ -9,11 +10,13 @@ use crate::{
 use clap::{Args, Parser};
 use giputils::TerminateCtrl;
 use log::{error, info};
-use logicrs::{Lit, LitVec, Var, VarRange, satif::Satif};
+use logicrs::{Lit, LitVec, satif::Satif};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::{
     ops::Deref,
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 #[derive(Args, Clone, Debug, Serialize, Deserialize)]
@@ -73,6 +76,9 @@ pub struct Kind {
     _ts: Box<NoDepTransys>,
     rst: Restore,
     tracer: Tracer,
+    /// number and total time of the solver calls
+    num_solve: usize,
+    solve_time: Duration,
     ctrl: Arc<KindCtrl>,
 }
 
@@ -130,6 +136,8 @@ impl Kind {
             _ts: ts,
             rst,
             tracer: Tracer::new(),
+            num_solve: 0,
+            solve_time: Duration::ZERO,
             ctrl: Arc::new(KindCtrl {
                 base: EngineCtrl::new(),
                 solver: Mutex::new(solver_ctrl),
@@ -164,6 +172,16 @@ impl Kind {
     }
 }
 
+impl Kind {
+    fn solve(&mut self, assump: &[Lit]) -> Option<bool> {
+        let start = Instant::now();
+        let res = self.solver.try_solve(assump, vec![]);
+        self.num_solve += 1;
+        self.solve_time += start.elapsed();
+        res
+    }
+}
+
 impl Engine for Kind {
     fn check(&mut self) -> McResult {
         for k in self.cfg.start..=self.cfg.end {
@@ -175,7 +193,7 @@ impl Engine for Kind {
             if k > 0 {
                 self.load_bad_to(k - 1);
                 let bad = self.get_bad(k);
-                let Some(res) = self.solver.try_solve(&[bad], vec![]) else {
+                let Some(res) = self.solve(&[bad]) else {
                     if self.ctrl.is_terminated() {
                         return McResult::Unknown(k.checked_sub(1));
                     }
@@ -189,7 +207,7 @@ impl Engine for Kind {
             if !self.cfg.skip_bmc {
                 let mut assump: LitVec = self.uts.ts.inits().iter().flatten().copied().collect();
                 assump.push(self.get_bad(k));
-                let Some(res) = self.solver.try_solve(&assump, vec![]) else {
+                let Some(res) = self.solve(&assump) else {
                     if self.ctrl.is_terminated() {
                         return McResult::Unknown(k.checked_sub(1));
                     }
@@ -210,6 +228,17 @@ impl Engine for Kind {
         self.tracer.add_tracer(tracer);
     }
 
+    fn report(&mut self, report: &mut Report) {
+        report.preproc_ts = Some(TsStatistic::new(self._ts.deref()));
+        report.statistic.insert(
+            "solver".to_string(),
+            json!({
+                "num_solve": self.num_solve,
+                "solve_time": self.solve_time.as_secs_f64(),
+            }),
+        );
+    }
+
     fn get_ctrl(&self) -> Arc<dyn TerminateCtrl> {
         self.ctrl.clone()
     }
@@ -222,128 +251,47 @@ impl BlEngine for Kind {
             error!("k-induction with simple path constraint not support certifaiger");
             panic!();
         }
+        if self.rst.enlarge() > 0 {
+            error!("k-induction with target enlargement not support certifaiger");
+            panic!();
+        }
         let mut ts = self.ots.clone();
+        self.rst.add_time_witness(&mut ts);
+        if let Some(prop) = self.cfg.prop {
+            if self.cfg.local_proof {
+                // a local proof assumes the other properties
+                let others: Vec<_> = ts
+                    .bad
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != prop)
+                    .map(|(_, &b)| !b)
+                    .collect();
+                ts.constraint.extend(others);
+            }
+            ts.bad = LitVec::from(ts.bad[prop]);
+        }
         let eqi = self.rst.eq_invariant();
         let mut certifaiger_dnf = vec![];
         for cube in eqi {
             certifaiger_dnf.push(ts.rel.new_and(cube));
         }
         certifaiger_dnf.extend(ts.bad);
-        let invariants = ts.rel.new_or(certifaiger_dnf);
-        ts.bad = LitVec::from(invariants);
-        if !ts.constraint.is_empty() {
-            ts.constraint = LitVec::from([ts.rel.new_and(ts.constraint)]);
-        }
-        let mut proof = ts.clone();
-        let ni = proof.input.len();
-        let nl = proof.latch.len();
-        let k = self.uts.num_unroll;
-        let mut inputs = proof.input.clone();
-        let mut latchs = proof.latch.clone();
-        let mut next = proof.next.clone();
-        let mut inits = proof.init.clone();
-        let mut bads = proof.bad.clone();
-        let mut constrains = proof.constraint.clone();
-        for _ in 1..k {
-            let offset = proof.max_var();
-            let map = |x: Var| {
-                if x == Var::CONST { x } else { x + offset }
-            };
-            proof.new_var_to(map(ts.max_var()));
-            let lmap = |x: Lit| Lit::new(map(x.var()), x.polarity());
-            for v in VarRange::new_inclusive(Var(1), ts.max_var()) {
-                let rel: Vec<LitVec> = ts.rel[v].iter().map(|cls| cls.map(lmap)).collect();
-                let mv = map(v);
-                proof.rel.add_rel(mv, &rel);
-            }
-            for &i in ts.input.iter() {
-                inputs.push(map(i));
-            }
-            for &l in ts.latch.iter() {
-                let ml = map(l);
-                latchs.push(ml);
-                next.insert(ml, lmap(ts.next[&l]));
-                if let Some(i) = ts.init.get(&l) {
-                    inits.insert(ml, lmap(*i));
-                }
-            }
-            bads.extend(ts.bad.map(lmap));
-            for &l in ts.constraint.iter() {
-                constrains.push(lmap(l));
-            }
-        }
-        if !constrains.is_empty() {
-            for i in 0..k {
-                bads[i] = proof.rel.new_or([bads[i], !constrains[i]]);
-            }
-        }
-        let sum = inputs.len() + latchs.len();
-        let mut aux_latchs: Vec<Lit> = Vec::new();
-        for i in 0..k {
-            let aux = proof.new_var().lit();
-            aux_latchs.push(aux);
-            let (next, init) = if i == 0 {
-                (aux, Some(Lit::constant(true)))
-            } else {
-                (aux_latchs[i - 1], Some(Lit::constant(false)))
-            };
-            proof.add_latch(aux.var(), init, next);
-        }
-        for i in 1..k {
-            for j in 0..ni {
-                proof.add_latch(inputs[j + i * ni], None, inputs[j + (i - 1) * ni].lit());
-            }
-            for j in 0..nl {
-                proof.add_latch(latchs[j + i * nl], None, latchs[j + (i - 1) * nl].lit());
-            }
-        }
-        for i in 0..k {
-            let al = aux_latchs[i];
-            let p = proof.rel.new_imply(al, !bads[i]);
-            bads[i] = !p;
-        }
-
-        for i in 1..k {
-            let al = aux_latchs[i];
-            let al_next = aux_latchs[i - 1];
-            let p = proof.rel.new_imply(al, al_next);
-            bads.push(!p);
-            let mut eqs = Vec::new();
-            let mut init = Vec::new();
-            for j in 0..nl {
-                let lis1j = latchs[(i - 1) * nl + j];
-                if let Some(&linit) = inits.get(&lis1j) {
-                    init.push(LitVec::from([lis1j.lit(), !linit]));
-                    init.push(LitVec::from([!lis1j.lit(), linit]));
-                }
-                eqs.push(
-                    proof
-                        .rel
-                        .new_xnor(next[&latchs[j + i * nl]], latchs[j + (i - 1) * nl].lit()),
-                );
-            }
-            let p = proof.rel.new_and(eqs);
-            let p = proof.rel.new_imply(al, p);
-            bads.push(!p);
-            let init: Vec<_> = init.into_iter().map(|cls| proof.rel.new_or(cls)).collect();
-            let init = proof.rel.new_and(init);
-            let p = proof.rel.new_and([!al, al_next]);
-            let p = proof.rel.new_imply(p, init);
-            bads.push(!p);
-        }
-        bads.push(!aux_latchs[0]);
-        proof.bad = LitVec::from(proof.rel.new_or(bads));
-        assert_eq!(proof.input.len() + proof.latch.len(), sum + k);
-        BlProof { proof }
+        ts.bad = LitVec::from(ts.rel.new_or(certifaiger_dnf));
+        // the equivalences may need more steps than the property
+        let k = self.uts.num_unroll.max(self.rst.eq_depth());
+        BlProof::k_induction(ts, k)
     }
 
     fn cex(&mut self) -> BlCex {
         let mut cex = self.uts.cex(self.solver.as_ref());
         cex = self.rst.restore_cex(&cex);
-        cex.exact_state(&self.ots, true);
         if let Some(prop) = self.cfg.prop {
             cex.bad_id = prop;
         }
+        if let Err(err) = cex.exact_state(&self.ots, true, self.rst.enlarge()) {
+            error!("failed to complete the cex: {err}");
+        }
         cex
     }
 }
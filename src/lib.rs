@@ -10,6 +10,7 @@ pub mod kind;
 pub mod mp;
 pub mod polynexus;
 pub mod portfolio;
+pub mod report;
 pub mod rlive;
 pub mod tracer;
 pub mod transys;
@@ -126,6 +127,9 @@ pub trait Engine: Send {
 
     fn statistic(&mut self) {}
 
+    /// Adds the engine statistics to a run report.
+    fn report(&mut self, _report: &mut report::Report) {}
+
     fn get_ctrl(&self) -> Arc<dyn TerminateCtrl> {
         panic!("unsupport get_ctrl");
     }
@@ -194,6 +198,17 @@ pub fn create_bl_engine(
     }
 }
 
+pub fn create_mp_engine(cfg: EngineConfig, ts: Transys) -> anyhow::Result<Box<dyn MpEngine>> {
+    Ok(match cfg {
+        EngineConfig::MultiProp(cfg) => Box::new(mp::MultiProp::new(cfg, ts)),
+        EngineConfig::Polynexus(cfg) => {
+            let results = MpMcResult::new(ts.bad.len());
+            Box::new(polynexus::PolyNexus::new(cfg, ts, results)?)
+        }
+        _ => unreachable!(),
+    })
+}
+
 pub fn create_wl_engine(cfg: EngineConfig, ts: WlTransys) -> Box<dyn WlEngine> {
     match cfg {
         EngineConfig::WlBMC(cfg) => Box::new(wlbmc::WlBMC::new(cfg, ts)),
@@ -0,0 +1,90 @@
+use crate::transys::{Transys, certify::Restore};
+use clap::{ArgAction, Args};
+use giputils::hash::GHashSet;
+use log::info;
+use logicrs::{Lit, LitVec, Var};
+use serde::{Deserialize, Serialize};
+
+#[derive(Args, Clone, Debug, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    /// cluster properties by cone-of-influence overlap and check each cluster
+    /// together on its reduced model
+    #[arg(long = "cluster", action = ArgAction::Set, default_value_t = false)]
+    pub cluster: bool,
+
+    /// minimum jaccard similarity of the latch cones in one cluster
+    #[arg(long = "cluster-jaccard", default_value_t = 0.8)]
+    pub cluster_jaccard: f64,
+
+    /// time limit in seconds of checking a cluster together, after which its
+    /// properties are checked one by one
+    #[arg(long = "cluster-tl", default_value_t = 60)]
+    pub cluster_tl: u64,
+}
+
+/// Latches in the cone of influence of a bad.
+fn latch_coi(ts: &Transys, bad: Lit) -> GHashSet<Var> {
+    let mut mark = GHashSet::from_iter([bad.var()]);
+    let mut queue = vec![bad.var()];
+    let mut latch = GHashSet::new();
+    while let Some(v) = queue.pop() {
+        let mut succ: Vec<Var> = ts.rel.dep(v).to_vec();
+        if let Some(n) = ts.next.get(&v) {
+            latch.insert(v);
+            succ.push(n.var());
+        }
+        for d in succ {
+            if mark.insert(d) {
+                queue.push(d);
+            }
+        }
+    }
+    latch
+}
+
+fn jaccard(x: &GHashSet<Var>, y: &GHashSet<Var>) -> f64 {
+    let union = x.union(y).count();
+    if union == 0 {
+        return 1.0;
+    }
+    x.intersection(y).count() as f64 / union as f64
+}
+
+/// Greedily groups the properties: each property joins the cluster whose
+/// latch cone is the most similar to its own, if the jaccard similarity
+/// reaches the threshold, and starts a new cluster otherwise.
+pub fn cluster_props(ts: &Transys, threshold: f64) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<(Vec<usize>, GHashSet<Var>)> = Vec::new();
+    for (p, &b) in ts.bad.iter().enumerate() {
+        let coi = latch_coi(ts, b);
+        let best = clusters
+            .iter()
+            .enumerate()
+            .map(|(i, (_, c))| (i, jaccard(&coi, c)))
+            .filter(|(_, s)| *s >= threshold)
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        match best {
+            Some((i, _)) => {
+                clusters[i].0.push(p);
+                clusters[i].1.extend(coi);
+            }
+            None => clusters.push((vec![p], coi)),
+        }
+    }
+    info!(
+        "cluster: {} properties in {} clusters",
+        ts.bad.len(),
+        clusters.len()
+    );
+    clusters.into_iter().map(|(c, _)| c).collect()
+}
+
+/// The model of a cluster: the bads of its properties, in order, on the cone
+/// of influence of them.
+pub fn cluster_ts(ts: &Transys, props: &[usize]) -> Transys {
+    let mut cts = ts.clone();
+    cts.bad = props.iter().map(|&p| ts.bad[p]).collect::<LitVec>();
+    let mut rst = Restore::new(&cts);
+    cts.coi_refine(&mut rst);
+    cts
+}
@@ -1,7 +1,12 @@
+mod cluster;
+mod test;
+
+pub use cluster::{ClusterConfig, cluster_props, cluster_ts};
+
 use std::sync::Arc;
 
 use crate::{
-    BlEngine, Engine, McResult, MpMcResult,
+    BlEngine, Engine, McResult, MpEngine, MpMcResult,
     config::{EngineConfigBase, PreprocConfig},
     ic3::{IC3, IC3Config},
     impl_config_deref,
@@ -25,7 +30,10 @@ pub struct MultiPropConfig {
     base: EngineConfigBase,
 
     #[command(flatten)]
-    preproc: PreprocConfig,
+    pub preproc: PreprocConfig,
+
+    #[command(flatten)]
+    cluster: ClusterConfig,
 
     /// Disable parallel checking
     #[arg(long = "no-parallel", action = ArgAction::SetFalse, default_value_t = true)]
@@ -48,7 +56,10 @@ pub struct MultiProp {
     ts: Transys,
     rst: Restore,
     ic3: Vec<IC3>,
+    /// the ic3 of each property in `ic3`
+    owner: Vec<usize>,
     ic3_cfg: IC3Config,
+    cluster: ClusterConfig,
     tracer: Tracer,
     parallel: bool,
     results: MpMcResult,
@@ -69,13 +80,16 @@ impl MultiProp {
         ic3_cfg.preproc.frts = false;
         ic3_cfg.preproc.scorr = false;
         let parallel = cfg.parallel;
+        let num_props = ts.bad.len();
         Self {
             ots,
-            results: MpMcResult::new(ts.bad.len()),
+            results: MpMcResult::new(num_props),
             ts,
             rst,
             ic3: Vec::new(),
+            owner: vec![0; num_props],
             ic3_cfg,
+            cluster: cfg.cluster,
             tracer: Tracer::new(),
             parallel,
             ctrl: Arc::new(EngineCtrl::new()),
@@ -83,45 +97,141 @@ impl MultiProp {
     }
 }
 
-impl Engine for MultiProp {
-    fn check(&mut self) -> McResult {
+impl MultiProp {
+    fn check_prop(ic3_cfg: &IC3Config, ts: &Transys, prop: usize) -> (IC3, McResult) {
+        let mut cfg = ic3_cfg.clone();
+        cfg.prop = Some(prop);
+        let mut ic3 = IC3::new(cfg, ts.clone(), VarSymbols::default());
+        let result = ic3.check();
+        (ic3, result)
+    }
+
+    /// Checks a cluster of properties together on its reduced model within
+    /// `time_limit` seconds, and falls back to one local proof per property if
+    /// one of them fails or the time is up.
+    fn check_cluster(
+        ic3_cfg: &IC3Config,
+        ts: &Transys,
+        props: &[usize],
+        time_limit: u64,
+    ) -> Vec<(Vec<usize>, IC3, McResult)> {
+        let cts = cluster_ts(ts, props);
+        let mut cfg = ic3_cfg.clone();
+        cfg.local_proof = false;
+        cfg.pred_prop = false;
+        if props.len() > 1 {
+            cfg.time_limit = Some(time_limit);
+        }
+        let mut ic3 = IC3::new(cfg, cts.clone(), VarSymbols::default());
+        let result = ic3.check();
+        if result.is_unsat() || props.len() == 1 {
+            return vec![(props.to_vec(), ic3, result)];
+        }
+        (0..props.len())
+            .map(|i| {
+                let (ic3, result) = Self::check_prop(ic3_cfg, &cts, i);
+                (vec![props[i]], ic3, result)
+            })
+            .collect()
+    }
+
+    fn check_job(
+        ic3_cfg: &IC3Config,
+        ts: &Transys,
+        cluster: Option<u64>,
+        props: &[usize],
+    ) -> Vec<(Vec<usize>, IC3, McResult)> {
+        if let Some(time_limit) = cluster {
+            Self::check_cluster(ic3_cfg, ts, props, time_limit)
+        } else {
+            let (ic3, result) = Self::check_prop(ic3_cfg, ts, props[0]);
+            vec![(props.to_vec(), ic3, result)]
+        }
+    }
+
+    /// Records the results of a job, returns whether a property fails.
+    fn record(&mut self, res: Vec<(Vec<usize>, IC3, McResult)>) -> bool {
+        let mut sat = false;
+        for (props, ic3, result) in res {
+            for &p in props.iter() {
+                self.owner[p] = self.ic3.len();
+                self.results[p] = result;
+                self.tracer.trace_state(Some(p), result);
+            }
+            self.ic3.push(ic3);
+            sat |= result.is_sat();
+        }
+        sat
+    }
+
+    /// Checks the properties in order. With `all` unset, stops at the first
+    /// violated property, otherwise the proofs of the holding properties do
+    /// not assume the violated ones.
+    fn check_props(&mut self, all: bool) -> MpMcResult {
+        let cluster = self.cluster.cluster.then_some(self.cluster.cluster_tl);
+        let jobs: Vec<Vec<usize>> = if cluster.is_some() {
+            cluster_props(&self.ts, self.cluster.cluster_jaccard)
+        } else {
+            (0..self.ts.bad.len()).map(|p| vec![p]).collect()
+        };
         if self.parallel {
             let results: Vec<_> = with_log_level(LevelFilter::Warn, || {
-                (0..self.ts.bad.len())
-                    .into_par_iter()
-                    .map(|i| {
-                        let mut cfg = self.ic3_cfg.clone();
-                        cfg.prop = Some(i);
-                        let mut ic3 = IC3::new(cfg, self.ts.clone(), VarSymbols::default());
-                        let result = ic3.check();
-                        (ic3, result)
-                    })
+                jobs.par_iter()
+                    .map(|job| Self::check_job(&self.ic3_cfg, &self.ts, cluster, job))
                     .collect()
             });
-            for (bad, (ic3, result)) in results.into_iter().enumerate() {
-                self.ic3.push(ic3);
-                self.results[bad] = result;
-                match result {
-                    McResult::UNSAT => (),
-                    McResult::SAT(_) => return result,
-                    McResult::Unknown(_) => unreachable!(),
+            for res in results {
+                if self.record(res) && !all {
+                    break;
                 }
             }
         } else {
-            for i in 0..self.ts.bad.len() {
-                let mut cfg = self.ic3_cfg.clone();
-                cfg.prop = Some(i);
-                let mut ic3 = IC3::new(cfg, self.ts.clone(), VarSymbols::default());
-                let result = ic3.check();
-                self.ic3.push(ic3);
-                self.results[i] = result;
-                match result {
-                    McResult::UNSAT => (),
-                    McResult::SAT(_) => return result,
-                    McResult::Unknown(_) => unreachable!(),
+            for job in jobs.iter() {
+                let res = Self::check_job(&self.ic3_cfg, &self.ts, cluster, job);
+                if self.record(res) && !all {
+                    break;
                 }
             }
         }
+        if all && self.results.iter().any(|r| r.is_sat()) {
+            self.reprove_local();
+        }
+        self.results.clone()
+    }
+
+    /// Proves the holding properties with local proofs again without
+    /// assuming the other properties, as a local proof is invalid once a
+    /// property it assumes is violated.
+    fn reprove_local(&mut self) {
+        let mut cfg = self.ic3_cfg.clone();
+        cfg.local_proof = false;
+        cfg.pred_prop = false;
+        let props: Vec<_> = (0..self.results.len())
+            .filter(|&p| self.results[p].is_unsat() && self.ic3[self.owner[p]].is_local_proof())
+            .collect();
+        let check = |&p: &usize| {
+            let (ic3, result) = Self::check_prop(&cfg, &self.ts, p);
+            (vec![p], ic3, result)
+        };
+        let res: Vec<_> = if self.parallel {
+            with_log_level(LevelFilter::Warn, || props.par_iter().map(check).collect())
+        } else {
+            props.iter().map(check).collect()
+        };
+        self.record(res);
+    }
+}
+
+impl Engine for MultiProp {
+    fn check(&mut self) -> McResult {
+        let results = self.check_props(false);
+        for result in results.iter() {
+            match result {
+                McResult::UNSAT => (),
+                McResult::SAT(_) => return *result,
+                McResult::Unknown(_) => unreachable!(),
+            }
+        }
         McResult::UNSAT
     }
 
@@ -148,7 +258,26 @@ impl BlEngine for MultiProp {
 
     fn cex(&mut self) -> BlCex {
         let bid = self.results.iter().position(|r| r.is_sat()).unwrap();
-        let cex = self.ic3[bid].cex();
-        self.rst.restore_cex(&cex)
+        MpEngine::cex(self, bid)
+    }
+}
+
+impl MpEngine for MultiProp {
+    fn check(&mut self) -> MpMcResult {
+        self.check_props(true)
+    }
+
+    fn proof(&mut self, prop: usize) -> BlProof {
+        let proof = self.ic3[self.owner[prop]].proof();
+        self.rst.restore_proof(proof, &self.ots)
+    }
+
+    fn cex(&mut self, prop: usize) -> BlCex {
+        let mut cex = self.rst.restore_cex(&self.ic3[self.owner[prop]].cex());
+        cex.bad_id = prop;
+        if let Err(err) = cex.exact_state(&self.ots, true, self.rst.enlarge()) {
+            error!("failed to complete the cex of b{prop}: {err}");
+        }
+        cex
     }
 }
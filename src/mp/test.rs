@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::{config::EngineConfig, transys::TransysIf};
+    use clap::Parser;
+    use logicrs::{Lit, LitVec};
+
+    /// Four free latches `a`, `b`, `c` and `d`, with the bads `a & b`,
+    /// `a | b`, `c & d` and `a`.
+    fn four_props() -> Transys {
+        let (mut ts, [], [a, b, c, d]) = Transys::test_model();
+        for l in [a, b, c, d] {
+            ts.add_latch(l, Some(Lit::constant(false)), l.lit());
+        }
+        let (a, b, c, d) = (a.lit(), b.lit(), c.lit(), d.lit());
+        ts.bad = LitVec::from([
+            ts.rel.new_and([a, b]),
+            ts.rel.new_or([a, b]),
+            ts.rel.new_and([c, d]),
+            a,
+        ]);
+        ts
+    }
+
+    #[test]
+    fn cluster_by_jaccard_of_latch_cones() {
+        let ts = four_props();
+        assert_eq!(cluster_props(&ts, 0.8), vec![vec![0, 1], vec![2], vec![3]]);
+        assert_eq!(cluster_props(&ts, 0.5), vec![vec![0, 1, 3], vec![2]]);
+        assert_eq!(cluster_props(&ts, 0.0), vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn cluster_ts_keeps_cluster_cone() {
+        let ts = four_props();
+        let cts = cluster_ts(&ts, &[3, 0]);
+        assert_eq!(cts.bad, LitVec::from([ts.bad[3], ts.bad[0]]));
+        assert_eq!(cts.latch.len(), 2);
+    }
+
+    #[test]
+    fn holding_proof_does_not_assume_violated_prop() {
+        let (mut ts, [i], [a, c]) = Transys::test_model();
+        ts.add_latch(a, Some(Lit::constant(false)), i.lit());
+        ts.add_latch(c, Some(Lit::constant(false)), c.lit());
+        ts.bad = LitVec::from([a.lit(), c.lit()]);
+        let cfg = EngineConfig::parse_from(["", "multi-prop", "--no-parallel"]);
+        let mut mp = MultiProp::new(cfg.into_multi_prop().unwrap(), ts);
+        let res = MpEngine::check(&mut mp);
+        assert!(res[0].is_sat() && res[1].is_unsat());
+        assert!(!mp.ic3[mp.owner[1]].is_local_proof());
+        assert!(MpEngine::proof(&mut mp, 1).proof.is_inductive());
+    }
+}
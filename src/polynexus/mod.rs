@@ -1,23 +1,25 @@
 mod schd;
+mod test;
 
 use crate::{
     BlCex, BlEngine, BlProof, Engine, McBlCertificate, McResult, MpEngine, MpMcResult,
-    config::{EngineConfigBase, PreprocConfig, WorkerConfigs},
-    ic3::{IC3, IC3Config},
-    impl_config_deref,
+    config::{EngineConfig, EngineConfigBase, PreprocConfig, WorkerConfigs},
+    create_bl_engine, impl_config_deref,
+    mp::{ClusterConfig, cluster_props, cluster_ts},
     polynexus::schd::Scheduler,
+    report::{Report, TsStatistic},
     tracer::{StateTracerIf, Tracer, TracerIf},
-    transys::{Transys, certify::Restore},
+    transys::{Transys, TransysIf, certify::Restore},
     utils::{EngineCtrl, StateIpcTx},
 };
-use clap::Args;
+use clap::{ArgAction, Args, Parser};
 use giputils::{TerminateCtrl, hash::GHashMap};
 use ipc_channel::{
     TrySelectError,
     ipc::{self, IpcReceiverSet, IpcSelectionResult, IpcSender},
 };
-use log::{LevelFilter, info, set_max_level};
-use logicrs::VarSymbols;
+use log::{LevelFilter, error, info, set_max_level};
+use logicrs::{LitVec, VarSymbols};
 use nix::{
     errno::Errno,
     sys::{
@@ -28,13 +30,14 @@ use nix::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
+    mem::take,
     process::exit,
     sync::Arc,
     thread,
     time::{Duration, Instant},
 };
 
-#[derive(Args, Clone, Debug, Serialize, Deserialize, Default)]
+#[derive(Args, Clone, Debug, Serialize, Deserialize)]
 pub struct PolyNexusConfig {
     #[command(flatten)]
     pub base: EngineConfigBase,
@@ -42,28 +45,136 @@ pub struct PolyNexusConfig {
     #[command(flatten)]
     pub preproc: PreprocConfig,
 
+    #[command(flatten)]
+    pub cluster: ClusterConfig,
+
     /// Number of worker processes (None = auto-detect)
     #[arg(long = "workers")]
     pub workers: Option<usize>,
+
+    /// Per-property time limit in seconds
+    #[arg(long = "prop-time-limit")]
+    pub prop_time_limit: Option<u64>,
+
+    /// Seconds without depth progress after which a property is deprioritized
+    #[arg(long = "stuck-time", default_value_t = 60)]
+    pub stuck_time: u64,
+
+    /// Assume the properties with smaller ids that are not violated, and the
+    /// invariants of the properties proved so far, when checking a property
+    #[arg(long = "local-proof", action = ArgAction::Set, default_value_t = true)]
+    pub local_proof: bool,
+
+    /// worker configuration, a builtin preset or a path to a TOML file of
+    /// presets
+    #[arg(long = "config")]
+    pub config: Option<String>,
+
+    /// preset of a worker configuration file with several presets, or a
+    /// builtin preset without `--config`
+    #[arg(long = "preset")]
+    pub preset: Option<String>,
+
+    /// resolved worker configuration, overriding `config`
+    #[arg(skip)]
+    pub worker_cfgs: Option<WorkerConfigs>,
+
+    /// settings of individual properties, by property id
+    #[arg(skip)]
+    pub prop_cfgs: GHashMap<usize, PropConfig>,
+}
+
+/// Settings of a single property, which is then checked on its own.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PropConfig {
+    /// workers of the property instead of the shared ones; the property
+    /// is given up once each of them ended without an answer
+    pub workers: Option<WorkerConfigs>,
+    /// time limit in seconds, instead of `--prop-time-limit`
+    pub time_limit: Option<u64>,
+    /// properties of higher priority are scheduled first
+    pub priority: i64,
 }
 
 impl_config_deref!(PolyNexusConfig);
 
+impl Default for PolyNexusConfig {
+    fn default() -> Self {
+        let cfg = EngineConfig::parse_from(["", "polynexus"]);
+        cfg.into_polynexus().unwrap()
+    }
+}
+
+impl PolyNexusConfig {
+    /// Loads and validates the worker configuration.
+    pub fn load_worker_cfgs(&mut self) -> anyhow::Result<()> {
+        let cfgs = match (self.worker_cfgs.take(), self.config.as_deref()) {
+            (Some(cfgs), _) => cfgs,
+            (None, None) => worker_preset(self.preset.as_deref().unwrap_or("bl_default"), None)?,
+            (None, Some(config)) => worker_preset(config, self.preset.as_deref())?,
+        };
+        check_worker_cfgs(&cfgs)?;
+        for cfg in self.prop_cfgs.values() {
+            if let Some(workers) = &cfg.workers {
+                check_worker_cfgs(workers)?;
+            }
+        }
+        self.worker_cfgs = Some(cfgs);
+        Ok(())
+    }
+}
+
+/// A builtin worker preset, or the preset `preset` of a TOML file of presets.
+pub fn worker_preset(config: &str, preset: Option<&str>) -> anyhow::Result<WorkerConfigs> {
+    WorkerConfigs::load(include_str!("config.toml"), config, preset)
+}
+
+/// PolyNexus workers check one property at a time, so they must be IC3,
+/// k-induction or BMC.
+pub fn check_worker_cfgs(cfgs: &WorkerConfigs) -> anyhow::Result<()> {
+    for (name, cfg) in cfgs.iter(false) {
+        if !matches!(
+            cfg,
+            EngineConfig::IC3(_) | EngineConfig::Kind(_) | EngineConfig::BMC(_)
+        ) {
+            anyhow::bail!(
+                "polynexus worker `{name}` uses {}, expected ic3, kind or bmc",
+                cfg.as_ref()
+            );
+        }
+    }
+    Ok(())
+}
+
 type WorkerDoneTx = IpcSender<WorkerDone>;
 
+/// The work of a worker process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct WorkerJob {
+    job: usize,
+    /// open properties of the job
+    props: Vec<usize>,
+    /// properties assumed to hold
+    deps: Vec<usize>,
+    /// whether the worker model only has the bads of `props`, in order
+    local: bool,
+    /// the worker configuration
+    engine: EngineConfig,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct WorkerDone {
-    prop: usize,
+    work: WorkerJob,
     result: McResult,
     cert: Option<McBlCertificate>,
 }
 
 struct RunningWorker {
-    prop: usize,
+    work: WorkerJob,
+    start: Instant,
 }
 
 pub struct PolyNexus {
-    #[allow(unused)]
     cfg: PolyNexusConfig,
     ots: Transys,
     ts: Transys,
@@ -72,17 +183,53 @@ pub struct PolyNexus {
     ctrl: Arc<EngineCtrl>,
     results: MpMcResult,
     certs: Vec<Option<McBlCertificate>>,
+    /// properties checked together by each job
+    jobs: Vec<Vec<usize>>,
+    /// the job of each property
+    job_of: Vec<usize>,
+    /// properties assumed by the proof of each property
+    deps: Vec<Vec<usize>>,
+    /// the worker configuration that resolved each property
+    solved_by: Vec<Option<EngineConfig>>,
 }
 
 impl PolyNexus {
-    pub fn new(cfg: PolyNexusConfig, ts: Transys, results: MpMcResult) -> Self {
+    pub fn new(mut cfg: PolyNexusConfig, ts: Transys, results: MpMcResult) -> anyhow::Result<Self> {
+        cfg.load_worker_cfgs()?;
         let ots = ts.clone();
         let rst = Restore::new(&ts);
         let (ts, mut rst) = ts.preproc(&cfg.preproc, rst);
         let mut ts = ts;
         ts.remove_gate_init(&mut rst);
         let num_props = ts.bad.len();
-        Self {
+        let mut jobs = if cfg.cluster.cluster {
+            cluster_props(&ts, cfg.cluster.cluster_jaccard)
+        } else {
+            (0..num_props).map(|p| vec![p]).collect()
+        };
+        if !cfg.prop_cfgs.is_empty() {
+            // properties with their own settings are checked alone
+            jobs = jobs
+                .into_iter()
+                .flat_map(|props| {
+                    let (own, rest): (Vec<_>, Vec<_>) = props
+                        .into_iter()
+                        .partition(|p| cfg.prop_cfgs.contains_key(p));
+                    let mut split: Vec<_> = own.into_iter().map(|p| vec![p]).collect();
+                    if !rest.is_empty() {
+                        split.push(rest);
+                    }
+                    split
+                })
+                .collect();
+        }
+        let mut job_of = vec![0; num_props];
+        for (j, props) in jobs.iter().enumerate() {
+            for &p in props {
+                job_of[p] = j;
+            }
+        }
+        Ok(Self {
             cfg,
             ots,
             ts,
@@ -91,6 +238,42 @@ impl PolyNexus {
             ctrl: Arc::new(EngineCtrl::new()),
             results,
             certs: vec![None; num_props],
+            job_of,
+            jobs,
+            deps: vec![Vec::new(); num_props],
+            solved_by: vec![None; num_props],
+        })
+    }
+
+    /// The worker configuration that resolved `prop` in this run.
+    pub fn solved_by(&self, prop: usize) -> Option<&EngineConfig> {
+        self.solved_by[prop].as_ref()
+    }
+
+    /// Whether [`MpEngine::proof`] is available for `prop`: it and the
+    /// properties its proof assumes were proved in this run.
+    pub fn has_proof(&self, prop: usize) -> bool {
+        matches!(self.certs[prop], Some(McBlCertificate::UNSAT(_)))
+            && self.deps[prop].iter().all(|&d| self.has_proof(d))
+    }
+
+    /// Properties of a job that are not resolved yet.
+    fn open_props(&self, job: usize) -> Vec<usize> {
+        self.jobs[job]
+            .iter()
+            .copied()
+            .filter(|&p| self.results[p].is_unknown())
+            .collect()
+    }
+
+    /// Sets the property of a worker config, which [`check_worker_cfgs`]
+    /// checked in [`PolyNexus::new`].
+    fn set_prop(cfg: &mut EngineConfig, prop: Option<usize>) {
+        match cfg {
+            EngineConfig::IC3(cfg) => cfg.prop = prop,
+            EngineConfig::Kind(cfg) => cfg.prop = prop,
+            EngineConfig::BMC(cfg) => cfg.prop = prop,
+            _ => unreachable!(),
         }
     }
 
@@ -103,8 +286,8 @@ impl PolyNexus {
     }
 
     fn spawn_worker(
-        prop: usize,
-        cfg: IC3Config,
+        work: WorkerJob,
+        cfg: EngineConfig,
         ts: &Transys,
         state_recv: &mut IpcReceiverSet,
         done_recv: &mut IpcReceiverSet,
@@ -119,18 +302,29 @@ impl PolyNexus {
                 let pid = Pid::from_raw(child);
                 let state_id = state_recv.add(state_rx).unwrap();
                 let done_id = done_recv.add(done_rx).unwrap();
-                running.insert(pid, RunningWorker { prop });
+                info!(
+                    "start polynexus worker j{} on {:?} assuming {:?}",
+                    work.job, work.props, work.deps
+                );
+                running.insert(
+                    pid,
+                    RunningWorker {
+                        work,
+                        start: Instant::now(),
+                    },
+                );
                 state_ids.insert(state_id, pid);
                 done_ids.insert(done_id, pid);
-                info!("start polynexus worker p{prop}");
             }
-            fork::Fork::Child => Self::run_worker(prop, cfg, ts, state_tx, done_tx),
+            fork::Fork::Child => Self::run_worker(work, cfg, ts, state_tx, done_tx),
         }
     }
 
+    /// Runs a worker on `ts`. A `local` worker checks all bads of `ts`, which
+    /// are the bads of its properties in order.
     fn run_worker(
-        prop: usize,
-        cfg: IC3Config,
+        work: WorkerJob,
+        cfg: EngineConfig,
         ts: &Transys,
         state_tx: StateIpcTx,
         done_tx: WorkerDoneTx,
@@ -139,20 +333,28 @@ impl PolyNexus {
         // We are in the forked child. Take ownership of the inherited model
         // directly, matching the portfolio worker isolation strategy.
         let ts = unsafe { std::ptr::read(ts) };
-        let mut ic3 = IC3::new(cfg, ts, VarSymbols::default());
-        ic3.add_tracer(Box::new(PropTracerBridge { prop, tx: state_tx }));
-        let result = ic3.check();
-        let cert = match result {
-            McResult::UNSAT => Some(McBlCertificate::UNSAT(ic3.proof())),
-            McResult::SAT(_) => Some(McBlCertificate::SAT(ic3.cex())),
-            McResult::Unknown(_) => None,
-        };
-        let _ = done_tx.send(WorkerDone { prop, result, cert });
+        let mut engine = create_bl_engine(cfg, ts, VarSymbols::default());
+        engine.add_tracer(Box::new(PropTracerBridge {
+            job: work.job,
+            tx: state_tx,
+        }));
+        let result = engine.check();
+        let mut cert = (!result.is_unknown()).then(|| engine.certificate(result));
+        if let Some(McBlCertificate::SAT(cex)) = cert.as_mut()
+            && work.local
+        {
+            cex.bad_id = work.props[cex.bad_id];
+        }
+        let _ = done_tx.send(WorkerDone { work, result, cert });
         exit(0);
     }
 
     fn terminate_running(running: &mut GHashMap<Pid, RunningWorker>) {
         let pids: Vec<_> = running.keys().copied().collect();
+        Self::terminate(running, pids);
+    }
+
+    fn terminate(running: &mut GHashMap<Pid, RunningWorker>, pids: Vec<Pid>) {
         for pid in &pids {
             let _ = kill(*pid, Signal::SIGTERM);
         }
@@ -184,8 +386,8 @@ impl PolyNexus {
                     Ok(WaitStatus::Exited(_, code)) => {
                         if code != 0 {
                             info!(
-                                "polynexus worker p{} exited with code {code}",
-                                running[&pid].prop
+                                "polynexus worker j{} exited with code {code}",
+                                running[&pid].work.job
                             );
                         }
                         running.remove(&pid);
@@ -193,8 +395,8 @@ impl PolyNexus {
                     }
                     Ok(WaitStatus::Signaled(_, sig, _)) => {
                         info!(
-                            "polynexus worker p{} terminated by {sig}",
-                            running[&pid].prop
+                            "polynexus worker j{} terminated by {sig}",
+                            running[&pid].work.job
                         );
                         running.remove(&pid);
                         break;
@@ -211,6 +413,58 @@ impl PolyNexus {
         }
     }
 
+    /// Number of running workers of each job.
+    fn busy(running: &GHashMap<Pid, RunningWorker>, num_jobs: usize) -> Vec<usize> {
+        let mut busy = vec![0; num_jobs];
+        for w in running.values() {
+            busy[w.work.job] += 1;
+        }
+        busy
+    }
+
+    /// Stops the workers of resolved, split or timed out jobs, the workers
+    /// that still check a resolved property or assume a violated one, and
+    /// preempts the longest running worker of a stuck job when the pool is
+    /// full.
+    fn rebalance(
+        &mut self,
+        num_workers: usize,
+        sched: &mut Scheduler,
+        running: &mut GHashMap<Pid, RunningWorker>,
+    ) {
+        for job in sched.timeout() {
+            info!("polynexus j{job} reached the per-property time limit");
+        }
+        if self.cfg.cluster.cluster {
+            self.split_clusters(sched);
+        }
+        let idle: Vec<_> = running
+            .iter()
+            .filter(|(_, w)| {
+                sched.resolved[w.work.job]
+                    || w.work.props.iter().any(|&p| !self.results[p].is_unknown())
+                    || w.work.deps.iter().any(|&p| self.results[p].is_sat())
+            })
+            .map(|(pid, _)| *pid)
+            .collect();
+        Self::terminate(running, idle);
+        if running.len() < num_workers {
+            return;
+        }
+        let busy = Self::busy(running, sched.num_jobs);
+        let Some(job) = sched.preempt(&busy) else {
+            return;
+        };
+        let pid = running
+            .iter()
+            .filter(|(_, w)| w.work.job == job)
+            .min_by_key(|(_, w)| w.start)
+            .map(|(pid, _)| *pid)
+            .unwrap();
+        info!("polynexus preempts a worker of stuck j{job}");
+        Self::terminate(running, vec![pid]);
+    }
+
     fn fill_workers(
         &mut self,
         num_workers: usize,
@@ -222,18 +476,75 @@ impl PolyNexus {
         done_ids: &mut GHashMap<u64, Pid>,
     ) {
         while running.len() < num_workers && !sched.all_resolved() {
-            let Some((prop, cfg)) = sched.pick() else {
+            let busy = Self::busy(running, sched.num_jobs);
+            let Some((job, _, mut cfg)) = sched.pick(&busy) else {
                 break;
             };
+            let props = self.open_props(job);
+            let local = self.cfg.cluster.cluster;
+            Self::set_prop(&mut cfg, (!local).then(|| props[0]));
+            // a worker always gives a certificate, which k-induction cannot
+            // for an enlarged target
+            if let EngineConfig::Kind(kind) = &mut cfg {
+                kind.preproc.enlarge = false;
+            }
+            let work = WorkerJob {
+                job,
+                deps: self.assumption(&props),
+                props,
+                local,
+                engine: cfg.clone(),
+            };
+            let wts = self.worker_ts(&work);
+            let ts = wts.as_ref().unwrap_or(&self.ts);
             Self::spawn_worker(
-                prop, cfg, &self.ts, state_recv, done_recv, running, state_ids, done_ids,
+                work, cfg, ts, state_recv, done_recv, running, state_ids, done_ids,
             );
         }
     }
 
+    /// The model of a worker: the model constrained by the properties it
+    /// assumes and by the invariants of those proved in this run. A local job
+    /// is checked on the reduced model of its open properties. None for the
+    /// model itself.
+    fn worker_ts(&self, work: &WorkerJob) -> Option<Transys> {
+        if !work.local && work.deps.is_empty() {
+            return None;
+        }
+        let mut ts = self.ts.clone();
+        ts.constraint
+            .extend(work.deps.iter().map(|&p| !self.ts.bad[p]));
+        for &p in work.deps.iter() {
+            if self.has_proof(p) {
+                self.assume_invariant(&mut ts, p);
+            }
+        }
+        Some(if work.local {
+            cluster_ts(&ts, &work.props)
+        } else {
+            ts
+        })
+    }
+
+    /// Adds the proof of `prop` to `ts` and constrains `ts` by its inductive
+    /// invariant, which holds in every reachable state.
+    fn assume_invariant(&self, ts: &mut Transys, prop: usize) {
+        let Some(McBlCertificate::UNSAT(proof)) = &self.certs[prop] else {
+            return;
+        };
+        let mut proof = proof.proof.clone();
+        let inv = !proof.rel.new_or(take(&mut proof.bad));
+        proof.constraint = LitVec::from([inv]);
+        ts.merge(&proof, |v| (v <= self.ts.max_var()).then_some(v));
+        // the merge only adds the constraints over new variables
+        if inv.var() <= self.ts.max_var() {
+            ts.constraint.push(inv);
+        }
+    }
+
     fn poll_state_traces(
         &mut self,
-        sched: &Scheduler,
+        sched: &mut Scheduler,
         unknown_bound: &mut Vec<Option<usize>>,
         state_recv: &mut IpcReceiverSet,
         state_ids: &mut GHashMap<u64, Pid>,
@@ -249,12 +560,20 @@ impl PolyNexus {
                     if !state_ids.contains_key(&id) {
                         continue;
                     }
-                    let (prop, result): (Option<usize>, McResult) = message.to().unwrap();
-                    let Some(prop) = prop else {
+                    let (job, result): (Option<usize>, McResult) = message.to().unwrap();
+                    let Some(job) = job else {
                         continue;
                     };
-                    if prop < sched.num_props && !sched.resolved[prop] {
-                        self.merge_and_trace(prop, result, unknown_bound);
+                    // final results are taken from the worker done messages,
+                    // together with their certificates
+                    if job < sched.num_jobs
+                        && !sched.resolved[job]
+                        && let McResult::Unknown(Some(d)) = result
+                    {
+                        sched.progress(job, d);
+                        for p in self.open_props(job) {
+                            self.merge_and_trace(p, result, unknown_bound);
+                        }
                     }
                 }
                 IpcSelectionResult::ChannelClosed(id) => {
@@ -292,36 +611,144 @@ impl PolyNexus {
         }
     }
 
+    /// Properties assumed when checking `props`: the properties with smaller
+    /// ids that are not violated, and the properties proved in this run
+    /// together with the properties their proofs assume. Neither can assume
+    /// `props`, which rules out circular reasoning, a proof is valid once the
+    /// properties it assumes are proved.
+    fn assumption(&self, props: &[usize]) -> Vec<usize> {
+        if !self.cfg.local_proof {
+            return Vec::new();
+        }
+        let min = props.iter().copied().min().unwrap();
+        (0..self.results.len())
+            .filter(|&p| (p < min && !self.results[p].is_sat()) || self.has_proof(p))
+            .collect()
+    }
+
+    /// Checks the open properties of the clusters that were checked together
+    /// for `cluster_tl` without an answer one by one.
+    fn split_clusters(&mut self, sched: &mut Scheduler) {
+        let limit = Duration::from_secs(self.cfg.cluster.cluster_tl);
+        for job in 0..sched.num_jobs {
+            let props = self.open_props(job);
+            if sched.resolved[job] || props.len() < 2 || !sched.ran_for(job, limit) {
+                continue;
+            }
+            info!("polynexus splits j{job}, it is not solved together in time");
+            let first = sched.split(job, props.len());
+            for (i, p) in props.into_iter().enumerate() {
+                self.job_of[p] = first + i;
+                self.jobs.push(vec![p]);
+            }
+        }
+    }
+
+    /// Reopens the properties whose proofs assumed the violated `prop`.
+    fn invalidate(&mut self, prop: usize, sched: &mut Scheduler) {
+        for p in 0..self.deps.len() {
+            if self.results[p].is_unsat() && self.deps[p].contains(&prop) {
+                info!("polynexus reopens p{p}, its proof assumed the violated p{prop}");
+                self.results[p] = McResult::Unknown(None);
+                self.certs[p] = None;
+                self.deps[p].clear();
+                self.solved_by[p] = None;
+                self.tracer.trace_state(Some(p), self.results[p]);
+                sched.reopen(self.job_of[p]);
+            }
+        }
+    }
+
+    /// Gives up the proofs that assume properties left unresolved.
+    fn settle(&mut self) {
+        for p in 0..self.deps.len() {
+            if self.results[p].is_unsat()
+                && self.deps[p].iter().any(|&d| !self.results[d].is_unsat())
+            {
+                info!("polynexus drops the proof of p{p}, an assumed property is unresolved");
+                self.results[p] = McResult::Unknown(None);
+                self.certs[p] = None;
+                self.deps[p].clear();
+                self.solved_by[p] = None;
+                self.tracer.trace_state(Some(p), self.results[p]);
+            }
+        }
+    }
+
     fn on_worker_done(
         &mut self,
         done: WorkerDone,
         sched: &mut Scheduler,
         unknown_bound: &mut Vec<Option<usize>>,
     ) {
-        let WorkerDone { prop, result, cert } = done;
-        if sched.resolved[prop] {
+        let WorkerDone { work, result, cert } = done;
+        let job = work.job;
+        // a result that assumes a violated property is dropped, but a cex
+        // stays valid without the assumptions
+        let stale = work.deps.iter().any(|&p| self.results[p].is_sat());
+        if sched.resolved[job] || (stale && !matches!(cert, Some(McBlCertificate::SAT(_)))) {
             return;
         }
-        if !result.is_unknown() {
-            sched.resolve(prop);
-            self.results[prop] = result;
-            self.tracer.trace_state(Some(prop), result);
-            let cert = cert.expect("polynexus worker returned a final result without certificate");
-            if let McBlCertificate::SAT(cex) = &cert {
-                let cex = self.rst.restore_cex(cex);
-                self.tracer.trace_cert(&McBlCertificate::SAT(cex));
+        let props: Vec<_> = work
+            .props
+            .into_iter()
+            .filter(|&p| self.results[p].is_unknown())
+            .collect();
+        match cert {
+            None => {
+                for p in props {
+                    self.merge_and_trace(p, result, unknown_bound);
+                }
+                if sched.give_up(job) {
+                    info!("polynexus gives up j{job}, each of its workers ended without an answer");
+                }
             }
-            self.certs[prop] = Some(cert);
-        } else {
-            self.merge_and_trace(prop, result, unknown_bound);
+            Some(McBlCertificate::UNSAT(proof)) => {
+                // a proof of a job covers all of its properties
+                for p in props {
+                    self.results[p] = result;
+                    self.tracer.trace_state(Some(p), result);
+                    self.certs[p] = Some(McBlCertificate::UNSAT(proof.clone()));
+                    self.deps[p] = work.deps.clone();
+                    self.solved_by[p] = Some(work.engine.clone());
+                }
+            }
+            Some(McBlCertificate::SAT(cex)) => {
+                let p = cex.bad_id;
+                if props.contains(&p) {
+                    self.results[p] = result;
+                    self.tracer.trace_state(Some(p), result);
+                    self.certs[p] = Some(McBlCertificate::SAT(cex));
+                    let rcex = MpEngine::cex(self, p);
+                    self.tracer.trace_cert(&McBlCertificate::SAT(rcex));
+                    self.solved_by[p] = Some(work.engine.clone());
+                    self.invalidate(p, sched);
+                }
+            }
+        }
+        if self.open_props(job).is_empty() {
+            sched.resolve(job);
         }
     }
 
     fn run(&mut self) -> MpMcResult {
         let num_workers = self.num_workers();
-        let presets = WorkerConfigs::from_toml(include_str!("config.toml"), "bl_default");
+        let presets = self.cfg.worker_cfgs.clone().unwrap();
         let num_props = self.ts.bad.len();
-        let mut sched = Scheduler::new(num_props, presets);
+        let mut sched = Scheduler::new(
+            self.jobs.len(),
+            presets,
+            self.cfg.prop_time_limit.map(Duration::from_secs),
+            Duration::from_secs(self.cfg.stuck_time),
+        );
+        for (&p, cfg) in self.cfg.prop_cfgs.iter() {
+            sched.configure(self.job_of[p], cfg);
+        }
+        for job in 0..self.jobs.len() {
+            if self.open_props(job).is_empty() {
+                sched.resolve(job);
+            }
+        }
         let mut state_recv = IpcReceiverSet::new().unwrap();
         let mut done_recv = IpcReceiverSet::new().unwrap();
         let mut running = GHashMap::new();
@@ -346,7 +773,12 @@ impl PolyNexus {
                 break;
             }
 
-            self.poll_state_traces(&sched, &mut unknown_bound, &mut state_recv, &mut state_ids);
+            self.poll_state_traces(
+                &mut sched,
+                &mut unknown_bound,
+                &mut state_recv,
+                &mut state_ids,
+            );
             self.poll_done(
                 &mut sched,
                 &mut unknown_bound,
@@ -360,6 +792,7 @@ impl PolyNexus {
                 &mut done_recv,
                 &mut done_ids,
             );
+            self.rebalance(num_workers, &mut sched, &mut running);
             if sched.all_resolved() {
                 break;
             }
@@ -377,6 +810,7 @@ impl PolyNexus {
         if !running.is_empty() {
             Self::terminate_running(&mut running);
         }
+        self.settle();
         self.results.clone()
     }
 
@@ -405,11 +839,11 @@ impl PolyNexus {
 }
 
 // ---------------------------------------------------------------------------
-// PropTracerBridge — forwards IC3 per-frame progress to the main thread.
+// PropTracerBridge — forwards worker per-frame progress to the main thread.
 // ---------------------------------------------------------------------------
 
 struct PropTracerBridge {
-    prop: usize,
+    job: usize,
     tx: StateIpcTx,
 }
 
@@ -418,7 +852,7 @@ impl TracerIf for PropTracerBridge {}
 #[intertrait::cast_to]
 impl StateTracerIf for PropTracerBridge {
     fn trace_state(&mut self, _prop: Option<usize>, res: McResult) {
-        let _ = self.tx.send((Some(self.prop), res));
+        let _ = self.tx.send((Some(self.job), res));
     }
 }
 
@@ -431,6 +865,10 @@ impl Engine for PolyNexus {
         self.tracer.add_tracer(tracer);
     }
 
+    fn report(&mut self, report: &mut Report) {
+        report.preproc_ts = Some(TsStatistic::new(&self.ts));
+    }
+
     fn get_ctrl(&self) -> Arc<dyn TerminateCtrl> {
         self.ctrl.clone()
     }
@@ -445,13 +883,24 @@ impl MpEngine for PolyNexus {
         let Some(McBlCertificate::UNSAT(proof)) = self.certs[prop].as_ref() else {
             panic!("no proof available for this property");
         };
-        self.rst.restore_proof(proof.clone(), &self.ots)
+        let mut proof = self.rst.restore_proof(proof.clone(), &self.ots);
+        // the proof is only valid together with the proofs it assumes
+        for d in self.deps[prop].clone() {
+            let subp = MpEngine::proof(self, d);
+            proof.merge(&subp, &self.ots);
+        }
+        proof
     }
 
     fn cex(&mut self, prop: usize) -> BlCex {
         let Some(McBlCertificate::SAT(cex)) = self.certs[prop].as_ref() else {
             panic!("no cex available for this property");
         };
-        self.rst.restore_cex(cex)
+        let mut cex = self.rst.restore_cex(cex);
+        cex.bad_id = prop;
+        if let Err(err) = cex.exact_state(&self.ots, true, self.rst.enlarge()) {
+            error!("failed to complete the cex of b{prop}: {err}");
+        }
+        cex
     }
 }
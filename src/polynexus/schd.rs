@@ -1,40 +1,181 @@
-use crate::{config::WorkerConfigs, ic3::IC3Config};
+use super::PropConfig;
+use crate::config::{EngineConfig, WorkerConfigs};
+use std::{
+    cmp::Reverse,
+    time::{Duration, Instant},
+};
 
+/// Difficulty-aware scheduler over jobs, each a property or a cluster of
+/// properties. Jobs without workers go first, jobs whose depth is still
+/// growing may get more workers, and jobs that have not made progress for
+/// `stuck_time` are deprioritized and can be preempted. Jobs of higher
+/// priority go before all others.
 pub struct Scheduler {
-    pub num_props: usize,
+    pub num_jobs: usize,
     pub resolved: Vec<bool>,
     config_counter: Vec<usize>,
-    configs: WorkerConfigs,
+    /// worker configs of each job
+    configs: Vec<WorkerConfigs>,
+    /// whether a job has its own worker configs
+    own_configs: Vec<bool>,
+    /// workers of each job that ended without an answer
+    gave_up: Vec<usize>,
+    priority: Vec<i64>,
+    /// first dispatch of each job
+    start: Vec<Option<Instant>>,
+    /// deepest bound reached by each job
+    depth: Vec<Option<usize>>,
+    /// when the depth last increased
+    progress: Vec<Option<Instant>>,
+    time_limit: Vec<Option<Duration>>,
+    stuck_time: Duration,
 }
 
 impl Scheduler {
-    pub fn new(num_props: usize, configs: WorkerConfigs) -> Self {
+    pub fn new(
+        num_jobs: usize,
+        configs: WorkerConfigs,
+        time_limit: Option<Duration>,
+        stuck_time: Duration,
+    ) -> Self {
         Self {
-            num_props,
-            resolved: vec![false; num_props],
-            config_counter: vec![0; num_props],
-            configs,
-        }
-    }
-
-    pub fn pick(&mut self) -> Option<(usize, IC3Config)> {
-        let prop = (0..self.num_props)
-            .filter(|&p| !self.resolved[p])
-            .min_by_key(|&p| self.config_counter[p])?;
-        let idx = self.config_counter[prop];
-        self.config_counter[prop] += 1;
-        let num_configs = self.configs.len();
-        assert!(num_configs > 0, "polynexus worker configs cannot be empty");
-        let (name, cfg) = self.configs.iter(true).nth(idx).unwrap();
-        let mut cfg = cfg
-            .into_ic3()
-            .unwrap_or_else(|_| panic!("polynexus worker config `{name}` is not ic3"));
-        cfg.prop = Some(prop);
-        Some((prop, cfg))
-    }
-
-    pub fn resolve(&mut self, prop: usize) {
-        self.resolved[prop] = true;
+            num_jobs,
+            resolved: vec![false; num_jobs],
+            config_counter: vec![0; num_jobs],
+            configs: vec![configs; num_jobs],
+            own_configs: vec![false; num_jobs],
+            gave_up: vec![0; num_jobs],
+            priority: vec![0; num_jobs],
+            start: vec![None; num_jobs],
+            depth: vec![None; num_jobs],
+            progress: vec![None; num_jobs],
+            time_limit: vec![time_limit; num_jobs],
+            stuck_time,
+        }
+    }
+
+    /// Applies the settings of the property of a single property job.
+    pub fn configure(&mut self, job: usize, cfg: &PropConfig) {
+        if let Some(workers) = &cfg.workers {
+            self.configs[job] = workers.clone();
+            self.own_configs[job] = true;
+        }
+        if let Some(t) = cfg.time_limit {
+            self.time_limit[job] = Some(Duration::from_secs(t));
+        }
+        self.priority[job] = cfg.priority;
+    }
+
+    fn is_stuck(&self, job: usize) -> bool {
+        self.progress[job]
+            .or(self.start[job])
+            .is_some_and(|t| t.elapsed() >= self.stuck_time)
+    }
+
+    fn is_growing(&self, job: usize) -> bool {
+        self.progress[job].is_some_and(|t| t.elapsed() < self.stuck_time)
+    }
+
+    /// Picks a job and a worker config, given the number of running
+    /// workers of each job.
+    pub fn pick(&mut self, busy: &[usize]) -> Option<(usize, String, EngineConfig)> {
+        let job = (0..self.num_jobs)
+            .filter(|&j| !self.resolved[j] && busy[j] < self.configs[j].len())
+            .min_by_key(|&j| {
+                (
+                    Reverse(self.priority[j]),
+                    self.is_stuck(j),
+                    busy[j].saturating_sub(self.is_growing(j) as usize),
+                    busy[j],
+                    self.config_counter[j],
+                )
+            })?;
+        let num_configs = self.configs[job].len();
+        let idx = self.config_counter[job];
+        self.config_counter[job] += 1;
+        self.start[job].get_or_insert_with(Instant::now);
+        let (name, cfg) = self.configs[job].iter(true).nth(idx % num_configs).unwrap();
+        Some((job, name, cfg))
+    }
+
+    pub fn progress(&mut self, job: usize, depth: usize) {
+        if self.depth[job].is_none_or(|d| depth > d) {
+            self.depth[job] = Some(depth);
+            self.progress[job] = Some(Instant::now());
+        }
+    }
+
+    /// A stuck job to take a worker from, if a job that is not
+    /// stuck is waiting for its first worker.
+    pub fn preempt(&self, busy: &[usize]) -> Option<usize> {
+        let waiting =
+            (0..self.num_jobs).any(|j| !self.resolved[j] && busy[j] == 0 && !self.is_stuck(j));
+        if !waiting {
+            return None;
+        }
+        (0..self.num_jobs)
+            .filter(|&j| busy[j] > 0 && self.is_stuck(j))
+            .max_by_key(|&j| busy[j])
+    }
+
+    /// Gives up the jobs that reached their time limit.
+    pub fn timeout(&mut self) -> Vec<usize> {
+        let timeout: Vec<_> = (0..self.num_jobs)
+            .filter(|&j| {
+                !self.resolved[j]
+                    && self.time_limit[j]
+                        .is_some_and(|limit| self.start[j].is_some_and(|s| s.elapsed() >= limit))
+            })
+            .collect();
+        for &j in timeout.iter() {
+            self.resolved[j] = true;
+        }
+        timeout
+    }
+
+    /// Whether `job` has been dispatched for at least `limit`.
+    pub fn ran_for(&self, job: usize, limit: Duration) -> bool {
+        self.start[job].is_some_and(|s| s.elapsed() >= limit)
+    }
+
+    /// Replaces `job` by `parts` new jobs with its settings, and returns the
+    /// id of the first one.
+    pub fn split(&mut self, job: usize, parts: usize) -> usize {
+        let first = self.num_jobs;
+        for _ in 0..parts {
+            self.resolved.push(false);
+            self.config_counter.push(0);
+            self.configs.push(self.configs[job].clone());
+            self.own_configs.push(self.own_configs[job]);
+            self.gave_up.push(0);
+            self.priority.push(self.priority[job]);
+            self.start.push(None);
+            self.depth.push(None);
+            self.progress.push(None);
+            self.time_limit.push(self.time_limit[job]);
+        }
+        self.num_jobs += parts;
+        self.resolved[job] = true;
+        first
+    }
+
+    pub fn resolve(&mut self, job: usize) {
+        self.resolved[job] = true;
+    }
+
+    /// Counts a worker of `job` that ended without an answer, and gives up
+    /// the job once each of its own worker configs did so.
+    pub fn give_up(&mut self, job: usize) -> bool {
+        self.gave_up[job] += 1;
+        if self.own_configs[job] && self.gave_up[job] >= self.configs[job].len() {
+            self.resolved[job] = true;
+        }
+        self.resolved[job]
+    }
+
+    pub fn reopen(&mut self, job: usize) {
+        self.resolved[job] = false;
+        self.gave_up[job] = 0;
     }
 
     pub fn all_resolved(&self) -> bool {
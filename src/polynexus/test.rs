@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use logicrs::Lit;
+    use std::fs;
+
+    fn workers() -> WorkerConfigs {
+        WorkerConfigs::from_toml("[w]\nic3 = \"ic3\"\nbmc = \"bmc\"\n", "w").unwrap()
+    }
+
+    #[test]
+    fn rejects_worker_of_other_engine() {
+        let cfg = PolyNexusConfig {
+            worker_cfgs: Some(WorkerConfigs::from_toml("[w]\nrlive = \"rlive\"\n", "w").unwrap()),
+            ..Default::default()
+        };
+        assert!(PolyNexus::new(cfg, Transys::default(), MpMcResult::new(0)).is_err());
+    }
+
+    #[test]
+    fn engine_worker_keeps_its_seed() {
+        let cfg = EngineConfig::parse_from(["", "ic3", "--rseed", "7"]);
+        let workers = WorkerConfigs::from_engine("ic3", cfg);
+        assert!(check_worker_cfgs(&workers).is_ok());
+        let (_, cfg) = workers.iter(true).next().unwrap();
+        assert_eq!(cfg.into_ic3().unwrap().rseed, 7);
+    }
+
+    #[test]
+    fn builtin_preset_is_selected() {
+        let mut cfg = PolyNexusConfig::default();
+        cfg.load_worker_cfgs().unwrap();
+        let workers = cfg.worker_cfgs.unwrap();
+        assert!(workers.iter(false).all(|(_, cfg)| cfg.is_ic3()));
+        let mut cfg = PolyNexusConfig {
+            preset: Some("bl_mixed".to_string()),
+            ..Default::default()
+        };
+        cfg.load_worker_cfgs().unwrap();
+        let workers = cfg.worker_cfgs.unwrap();
+        assert!(workers.iter(false).any(|(_, cfg)| cfg.is_bmc()));
+    }
+
+    #[test]
+    fn user_file_preset_is_selected() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            file.path(),
+            "[a]\nic3 = \"ic3\"\n[b]\nbmc = \"bmc\"\nkind = \"kind\"\n",
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+        assert!(worker_preset(path, None).is_err());
+        assert_eq!(worker_preset(path, Some("b")).unwrap().len(), 2);
+        assert!(worker_preset(path, Some("c")).is_err());
+        assert!(worker_preset("bl_default", Some("b")).is_err());
+        fs::write(file.path(), "[a]\nic3 = \"ic3\"\n").unwrap();
+        assert_eq!(worker_preset(path, None).unwrap().len(), 1);
+    }
+
+    /// The latches `a` and `b` and the input `x`, with the bads `b` and
+    /// `a & x`. The proof of the second bad has the invariant `!a`, relative
+    /// to which the first bad is inductive.
+    #[test]
+    fn worker_assumes_invariant_of_proved_prop() {
+        let (mut ts, [x], [a, b]) = Transys::test_model();
+        ts.add_latch(a, Some(Lit::constant(false)), a.lit());
+        let ab = ts.rel.new_or([a.lit(), b.lit()]);
+        ts.add_latch(b, Some(Lit::constant(false)), ab);
+        let ax = ts.rel.new_and([a.lit(), x.lit()]);
+        ts.bad = LitVec::from([b.lit(), ax]);
+        let mut cfg = PolyNexusConfig {
+            worker_cfgs: Some(workers()),
+            ..Default::default()
+        };
+        cfg.preproc.preproc = false;
+        let mut nexus = PolyNexus::new(cfg, ts.clone(), MpMcResult::new(2)).unwrap();
+        let ic3 = EngineConfig::parse_from(["", "ic3", "--prop", "1", "--preproc", "false"]);
+        let mut engine = create_bl_engine(ic3.clone(), ts.clone(), VarSymbols::default());
+        let res = engine.check();
+        assert!(res.is_unsat());
+        nexus.results[1] = res;
+        nexus.certs[1] = Some(engine.certificate(res));
+        let work = WorkerJob {
+            job: 0,
+            props: vec![0],
+            deps: nexus.assumption(&[0]),
+            local: false,
+            engine: ic3,
+        };
+        assert_eq!(work.deps, vec![1]);
+        let mut wts = nexus.worker_ts(&work).unwrap();
+        wts.bad = LitVec::from([b.lit()]);
+        assert!(wts.is_inductive());
+        ts.constraint.push(!ax);
+        ts.bad = LitVec::from([b.lit()]);
+        assert!(!ts.is_inductive());
+    }
+
+    #[test]
+    fn scheduler_preempts_stuck_job_for_waiting_job() {
+        let mut sched = Scheduler::new(2, workers(), None, Duration::ZERO);
+        assert_eq!(sched.preempt(&[0, 0]), None);
+        let (job, _, _) = sched.pick(&[0, 0]).unwrap();
+        assert_eq!(job, 0);
+        assert_eq!(sched.preempt(&[1, 0]), Some(0));
+        let (job, _, _) = sched.pick(&[1, 0]).unwrap();
+        assert_eq!(job, 1);
+        assert_eq!(sched.preempt(&[1, 1]), None);
+    }
+
+    #[test]
+    fn scheduler_keeps_growing_job() {
+        let mut sched = Scheduler::new(2, workers(), None, Duration::from_secs(3600));
+        sched.pick(&[0, 0]).unwrap();
+        sched.progress(0, 3);
+        assert_eq!(sched.preempt(&[1, 0]), None);
+    }
+
+    #[test]
+    fn scheduler_splits_job() {
+        let mut sched = Scheduler::new(1, workers(), None, Duration::from_secs(3600));
+        assert!(!sched.ran_for(0, Duration::ZERO));
+        sched.pick(&[0]).unwrap();
+        assert!(sched.ran_for(0, Duration::ZERO));
+        assert_eq!(sched.split(0, 2), 1);
+        assert_eq!(sched.num_jobs, 3);
+        assert!(sched.resolved[0] && !sched.all_resolved());
+        assert!(sched.pick(&[1, 0, 0]).is_some_and(|(job, _, _)| job == 1));
+    }
+
+    #[test]
+    fn scheduler_times_out_started_jobs() {
+        let mut sched = Scheduler::new(
+            2,
+            workers(),
+            Some(Duration::ZERO),
+            Duration::from_secs(3600),
+        );
+        sched.configure(
+            1,
+            &PropConfig {
+                time_limit: Some(3600),
+                ..Default::default()
+            },
+        );
+        assert!(sched.timeout().is_empty());
+        sched.pick(&[0, 0]).unwrap();
+        sched.pick(&[1, 0]).unwrap();
+        assert_eq!(sched.timeout(), vec![0]);
+        assert!(sched.resolved[0] && !sched.resolved[1]);
+        assert!(sched.pick(&[0, 1]).is_some_and(|(job, _, _)| job == 1));
+    }
+}
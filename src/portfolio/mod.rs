@@ -4,35 +4,43 @@ mod ui;
 use self::lemma_mgr::LemmaMgr;
 use self::ui::PortfolioUi;
 use crate::config::{EngineConfig, EngineConfigBase, PreprocConfig, WorkerConfigs};
+use crate::report::{Report, TsStatistic, WorkerReport};
 use crate::tracer::{Tracer, TracerIf};
 use crate::transys::Transys;
 use crate::transys::certify::{BlCex, BlProof, Restore};
-use crate::ui::UiRenderer;
-use crate::utils::{
-    CertIpcRx, CertIpcTx, EngineCtrl, LemmaIpcRx, StateIpcTx, install_interrupt_handler,
-};
+use crate::ui::{UiRenderer, restore_terminal};
+use crate::utils::{EngineCtrl, ForkInterrupt, LemmaIpcRx, StateIpcTx};
 use crate::{BlEngine, Engine, McBlCertificate, McResult, create_bl_engine, impl_config_deref};
 use anyhow::Context;
 use clap::{Args, Parser};
 use giputils::TerminateCtrl;
 use giputils::hash::GHashMap;
 use giputils::logger::with_log_level;
-use ipc_channel::ipc;
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::{
     TrySelectError,
     ipc::{IpcReceiverSet, IpcSelectionResult},
 };
-use log::{LevelFilter, info, set_max_level};
+use log::{LevelFilter, error, info, set_max_level, warn};
 use logicrs::VarSymbols;
 use nix::errno::Errno;
-use nix::sys::wait::{WaitPidFlag, WaitStatus, waitpid};
+use nix::sys::resource::{Resource, setrlimit};
+use nix::sys::signal::{Signal, kill};
+use nix::sys::wait::{WaitStatus, waitpid};
 use nix::unistd::Pid;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::iter;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::{process::exit, sync::mpsc, thread::spawn};
+use std::{
+    mem,
+    process::exit,
+    sync::mpsc,
+    thread::{sleep, spawn},
+};
 use tempfile::TempDir;
 
 #[derive(Args, Clone, Debug, Serialize, Deserialize)]
@@ -43,13 +51,28 @@ pub struct PortfolioConfig {
     #[command(flatten)]
     pub preproc: PreprocConfig,
 
-    /// worker configuration
+    /// worker configuration, a builtin preset or a path to a TOML file of
+    /// presets
     #[arg(long = "config")]
     pub config: Option<String>,
 
+    /// preset of a worker configuration file with several presets
+    #[arg(long = "preset", requires = "config")]
+    pub preset: Option<String>,
+
     /// share lemma
     #[arg(long = "share-lemma")]
     pub share_lemma: bool,
+
+    /// memory limit of the data segment of each worker in MiB, a worker that
+    /// exceeds it is out of memory
+    #[arg(long = "worker-mem-limit")]
+    pub worker_mem_limit: Option<usize>,
+
+    /// memory limit of all workers in MiB, the largest worker is terminated
+    /// when it is exceeded
+    #[arg(long = "mem-limit")]
+    pub mem_limit: Option<usize>,
 }
 
 impl_config_deref!(PortfolioConfig);
@@ -61,6 +84,11 @@ impl Default for PortfolioConfig {
     }
 }
 
+/// A builtin worker preset, or the preset `preset` of a TOML file of presets.
+pub fn worker_preset(config: &str, preset: Option<&str>) -> anyhow::Result<WorkerConfigs> {
+    WorkerConfigs::load(include_str!("portfolio.toml"), config, preset)
+}
+
 pub struct Portfolio {
     ots: Transys,
     ts: Transys,
@@ -68,6 +96,8 @@ pub struct Portfolio {
     rst: Restore,
     cert: Option<McBlCertificate>,
     need_cert: bool,
+    /// engine statistics of the winning worker
+    statistic: BTreeMap<String, Value>,
     cfg: PortfolioConfig,
     engines: Vec<Worker>,
     running: GHashMap<Pid, usize>,
@@ -80,15 +110,68 @@ pub struct Portfolio {
     st_recv: IpcReceiverSet,
     // state tracer id to worker id
     stid_to_wid: GHashMap<u64, usize>,
+    // memory report id to worker id
+    memid_to_wid: GHashMap<u64, usize>,
+    interrupted: bool,
 }
 
 struct Worker {
     name: String,
     cfg: EngineConfig,
     args: String,
-    cert_tx: Option<CertIpcTx>,
-    cert_rx: Option<CertIpcRx>,
+    result_tx: IpcSender<WorkerResult>,
+    result_rx: IpcReceiver<WorkerResult>,
     state: McResult,
+    /// resident memory in bytes, as last reported
+    mem: usize,
+    memout: bool,
+}
+
+/// Sent by a worker when it finishes: its certificate if one is needed, and
+/// its engine statistics as JSON text, which the IPC encoding cannot decode
+/// as values.
+#[derive(Serialize, Deserialize)]
+struct WorkerResult {
+    cert: Option<McBlCertificate>,
+    statistic: String,
+}
+
+/// Caps the data segment of the forked worker at `limit` MiB, and reports
+/// its resident memory to the portfolio for the global memory limit. An
+/// allocation over the cap aborts the worker.
+fn monitor_memory(limit: Option<usize>, tx: IpcSender<usize>) {
+    if let Some(limit) = limit {
+        let bytes = (limit as u64) << 20;
+        if let Err(err) = setrlimit(Resource::RLIMIT_DATA, bytes, bytes) {
+            warn!("failed to set worker memory limit: {err}");
+        }
+    }
+    spawn(move || {
+        loop {
+            let Some(stats) = memory_stats::memory_stats() else {
+                return;
+            };
+            if tx.send(stats.physical_mem).is_err() {
+                return;
+            }
+            sleep(Duration::from_millis(500));
+        }
+    });
+}
+
+/// Reaps a terminated worker without blocking, like `waitpid`, with its peak
+/// resident memory in bytes.
+fn wait_worker() -> nix::Result<(WaitStatus, usize)> {
+    let mut status = 0;
+    let mut usage: libc::rusage = unsafe { mem::zeroed() };
+    let pid = unsafe { libc::wait4(-1, &mut status, libc::WNOHANG, &mut usage) };
+    let pid = Errno::result(pid)?;
+    if pid == 0 {
+        return Ok((WaitStatus::StillAlive, 0));
+    }
+    let status = WaitStatus::from_raw(Pid::from_raw(pid), status)?;
+    // ru_maxrss is in KiB on Linux
+    Ok((status, usage.ru_maxrss as usize * 1024))
 }
 
 impl Worker {
@@ -100,6 +183,7 @@ impl Worker {
         sym: &VarSymbols,
         tracer: StateIpcTx,
         extractor: Option<LemmaIpcRx>,
+        need_cert: bool,
     ) -> ! {
         set_max_level(LevelFilter::Warn);
         // We are already in the forked child, so take ownership of the inherited
@@ -110,20 +194,21 @@ impl Worker {
         engine.add_tracer(Box::new(tracer));
         extractor.map(|e| engine.set_extractor(Box::new(e)));
         let res = engine.check();
-        if let Some(cert_tx) = self.cert_tx.as_ref() {
-            let certificate = match res {
-                McResult::UNSAT => {
-                    let cert = rst.restore_proof(engine.proof(), ots);
-                    McBlCertificate::UNSAT(cert)
-                }
-                McResult::SAT(_) => {
-                    let cert = rst.restore_cex(&engine.cex());
-                    McBlCertificate::SAT(cert)
+        let cert = (need_cert && !res.is_unknown()).then(|| match res {
+            McResult::UNSAT => McBlCertificate::UNSAT(rst.restore_proof(engine.proof(), ots)),
+            McResult::SAT(_) => {
+                let mut cex = rst.restore_cex(&engine.cex());
+                if let Err(err) = cex.exact_state(ots, true, rst.enlarge()) {
+                    error!("failed to complete the cex: {err}");
                 }
-                McResult::Unknown(_) => panic!(),
-            };
-            let _ = cert_tx.send(certificate);
-        };
+                McBlCertificate::SAT(cex)
+            }
+            McResult::Unknown(_) => unreachable!(),
+        });
+        let mut report = Report::new(self.cfg.as_ref());
+        engine.report(&mut report);
+        let statistic = serde_json::to_string(&report.statistic).unwrap();
+        let _ = self.result_tx.send(WorkerResult { cert, statistic });
         exit(0);
     }
 }
@@ -142,26 +227,27 @@ impl Portfolio {
         let mut engines = Vec::new();
         let mut new_engine = |name, args: &str| {
             let argv: Vec<_> = iter::once("").chain(args.split_whitespace()).collect();
-            let cfg = EngineConfig::try_parse_from(argv)?;
+            let mut cfg = EngineConfig::try_parse_from(argv)?;
             assert!(!cfg.is_wl());
-            let (cert_tx, cert_rx) = if need_cert {
-                let (cert_tx, cert_rx) = ipc::channel().unwrap();
-                (Some(cert_tx), Some(cert_rx))
-            } else {
-                (None, None)
-            };
+            // k-induction gives no proof of an enlarged target
+            if need_cert && let EngineConfig::Kind(kind) = &mut cfg {
+                kind.preproc.enlarge = false;
+            }
+            let (result_tx, result_rx) = ipc::channel().unwrap();
             engines.push(Worker {
                 name,
                 cfg,
                 args: args.to_string(),
-                cert_tx,
-                cert_rx,
+                result_tx,
+                result_rx,
                 state: McResult::default(),
+                mem: 0,
+                memout: false,
             });
             anyhow::Ok(())
         };
         let config = cfg.config.as_deref().unwrap_or("bl_default");
-        let worker_cfgs = WorkerConfigs::from_toml(include_str!("portfolio.toml"), config);
+        let worker_cfgs = worker_preset(config, cfg.preset.as_deref())?;
         for (name, args) in worker_cfgs.iter_args(true) {
             new_engine(name.clone(), &args)
                 .with_context(|| format!("invalid portfolio worker `{name}`"))?;
@@ -173,6 +259,7 @@ impl Portfolio {
             rst,
             cert: None,
             need_cert,
+            statistic: BTreeMap::new(),
             cfg,
             engines,
             running: GHashMap::new(),
@@ -183,6 +270,8 @@ impl Portfolio {
             ui: None,
             st_recv: IpcReceiverSet::new().unwrap(),
             stid_to_wid: GHashMap::new(),
+            memid_to_wid: GHashMap::new(),
+            interrupted: false,
         })
     }
 
@@ -246,13 +335,9 @@ impl Portfolio {
             worker.name, worker.args
         );
         self.winner_idx = Some(worker_idx);
-        if self.need_cert {
-            let cert = self.engines[worker_idx]
-                .cert_rx
-                .as_mut()
-                .unwrap()
-                .recv()
-                .unwrap();
+        let result = self.engines[worker_idx].result_rx.recv().unwrap();
+        self.statistic = serde_json::from_str(&result.statistic).unwrap();
+        if let Some(cert) = result.cert {
             self.tracer.trace_cert(&cert);
             self.cert = Some(cert);
         }
@@ -260,9 +345,9 @@ impl Portfolio {
 
     fn reap_child(&mut self) -> Option<McResult> {
         loop {
-            match waitpid(None, Some(WaitPidFlag::WNOHANG)) {
-                Ok(WaitStatus::StillAlive) => break,
-                Ok(WaitStatus::Exited(pid, code)) => {
+            match wait_worker() {
+                Ok((WaitStatus::StillAlive, _)) => break,
+                Ok((WaitStatus::Exited(pid, code), _)) => {
                     let worker_idx = self.running.remove(&pid).unwrap();
                     if code == 0 {
                         while self.winner_idx.is_none() {
@@ -277,8 +362,23 @@ impl Portfolio {
                         info!("{} exited with code {code}", self.engines[worker_idx].name);
                     }
                 }
-                Ok(WaitStatus::Signaled(pid, _, _)) => {
-                    self.running.remove(&pid);
+                Ok((WaitStatus::Signaled(pid, sig, _), peak)) => {
+                    let worker_idx = self.running.remove(&pid).unwrap();
+                    // a worker is out of memory when it is killed for the
+                    // global limit, or aborted by an allocation over its own.
+                    // Growing data doubles, so an allocation over the cap
+                    // fails once at least half of it is in use, an earlier
+                    // abort is a failure of the worker itself.
+                    let rlimit = sig == Signal::SIGABRT
+                        && self
+                            .cfg
+                            .worker_mem_limit
+                            .is_some_and(|limit| peak >= (limit << 20) / 2);
+                    if self.engines[worker_idx].memout || rlimit {
+                        self.on_memout(worker_idx);
+                    } else {
+                        info!("{} terminated by {sig}", self.engines[worker_idx].name);
+                    }
                 }
                 Err(Errno::EINTR) => continue,
                 Err(Errno::ECHILD) => break,
@@ -289,6 +389,86 @@ impl Portfolio {
         None
     }
 
+    /// Whether the last check was interrupted by Ctrl-C.
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted
+    }
+
+    /// The deepest bound reached by any worker.
+    fn best_bound(&self) -> McResult {
+        let depth = self
+            .engines
+            .iter()
+            .filter_map(|w| w.state.into_unknown().ok().flatten())
+            .max();
+        McResult::Unknown(depth)
+    }
+
+    /// One line per worker with its result and memory.
+    pub fn summary(&self) -> String {
+        let mut summary = String::new();
+        for (idx, worker) in self.engines.iter().enumerate() {
+            let res = match worker.state {
+                McResult::UNSAT => "UNSAT".to_string(),
+                McResult::SAT(d) => format!("SAT at depth {d}"),
+                McResult::Unknown(Some(d)) => format!("bound {d}"),
+                McResult::Unknown(None) => "-".to_string(),
+            };
+            let mem = if worker.memout {
+                "memout".to_string()
+            } else {
+                format!("{} MiB", worker.mem >> 20)
+            };
+            summary.push_str(&format!("{idx:>3} {:<24} {res:<16} {mem}\n", worker.name));
+        }
+        summary
+    }
+
+    fn on_memout(&mut self, worker_idx: usize) {
+        let worker = &mut self.engines[worker_idx];
+        worker.memout = true;
+        worker.mem = 0;
+        info!("{} ran out of memory, reported as unknown", worker.name);
+        if let Some(ui) = self.ui.as_mut() {
+            ui.memout(worker_idx);
+        }
+    }
+
+    fn on_memory(&mut self, worker_idx: usize, mem: usize) {
+        self.engines[worker_idx].mem = mem;
+        if let Some(ui) = self.ui.as_mut() {
+            ui.update_mem(worker_idx, mem);
+        }
+    }
+
+    /// Terminates the worker with the largest memory while all workers
+    /// together exceed the global memory limit.
+    fn enforce_mem_limit(&mut self) {
+        let Some(limit) = self.cfg.mem_limit else {
+            return;
+        };
+        let total: usize = self.running.values().map(|&w| self.engines[w].mem).sum();
+        if total <= limit << 20 {
+            return;
+        }
+        let Some((&pid, &worker_idx)) = self
+            .running
+            .iter()
+            .max_by_key(|(_, w)| self.engines[**w].mem)
+        else {
+            return;
+        };
+        let worker = &mut self.engines[worker_idx];
+        info!(
+            "workers use {} MiB over the memory limit, terminate {}",
+            total >> 20,
+            worker.name
+        );
+        worker.memout = true;
+        worker.mem = 0;
+        let _ = kill(pid, Signal::SIGKILL);
+    }
+
     fn poll_state_traces(&mut self) {
         let events = match self.st_recv.try_select_timeout(Duration::from_millis(100)) {
             Ok(events) => events,
@@ -298,6 +478,11 @@ impl Portfolio {
         for event in events {
             match event {
                 IpcSelectionResult::MessageReceived(id, message) => {
+                    if let Some(&worker_idx) = self.memid_to_wid.get(&id) {
+                        let mem: usize = message.to().unwrap();
+                        self.on_memory(worker_idx, mem);
+                        continue;
+                    }
                     let Some(&worker_idx) = self.stid_to_wid.get(&id) else {
                         continue;
                     };
@@ -306,6 +491,7 @@ impl Portfolio {
                 }
                 IpcSelectionResult::ChannelClosed(id) => {
                     self.stid_to_wid.remove(&id);
+                    self.memid_to_wid.remove(&id);
                 }
             }
         }
@@ -315,8 +501,12 @@ impl Portfolio {
 impl Engine for Portfolio {
     fn check(&mut self) -> McResult {
         let mut lemma_mgr = self.cfg.share_lemma.then(LemmaMgr::new);
+        // installed before forking, the workers ignore Ctrl-C and are stopped
+        // by the portfolio
+        let interrupt = ForkInterrupt::install();
         for (worker_idx, worker) in self.engines.iter_mut().enumerate() {
             let (state_tx, state_rx) = ipc::channel().unwrap();
+            let (mem_tx, mem_rx) = ipc::channel().unwrap();
             let (lemma_send, lemma_recv) = if self.cfg.share_lemma {
                 let (lemma_send, lemma_recv) = ipc::channel().unwrap();
                 (Some(lemma_send), Some(lemma_recv))
@@ -326,6 +516,7 @@ impl Engine for Portfolio {
             match fork::fork().unwrap() {
                 fork::Fork::Parent(child) => {
                     let state_trace_id = self.st_recv.add(state_rx).unwrap();
+                    let mem_id = self.st_recv.add(mem_rx).unwrap();
                     lemma_mgr.as_mut().map(|lemma_mgr| {
                         lemma_mgr
                             .add_worker(
@@ -339,28 +530,44 @@ impl Engine for Portfolio {
                     info!("start engine {}", worker.name);
                     self.running.insert(pid, worker_idx);
                     self.stid_to_wid.insert(state_trace_id, worker_idx);
+                    self.memid_to_wid.insert(mem_id, worker_idx);
                 }
                 fork::Fork::Child => {
+                    ForkInterrupt::ignore_in_child();
+                    monitor_memory(self.cfg.worker_mem_limit, mem_tx);
                     worker.run(
-                        &self.ts, &self.ots, &self.rst, &self.sym, state_tx, lemma_recv,
+                        &self.ts,
+                        &self.ots,
+                        &self.rst,
+                        &self.sym,
+                        state_tx,
+                        lemma_recv,
+                        self.need_cert,
                     );
                 }
             }
         }
         let lemma_mgr_join = lemma_mgr.map(|lemma_mgr| spawn(move || lemma_mgr.run()));
-        let interrupt = install_interrupt_handler(self.ctrl.clone());
 
         let start = Instant::now();
         loop {
+            if interrupt.is_interrupted() {
+                self.terminate_running();
+                let _ = lemma_mgr_join.map(|j| j.join());
+                let res = self.best_bound();
+                if let Some(ui) = self.ui.as_ref() {
+                    ui.finish(res);
+                }
+                restore_terminal();
+                self.interrupted = true;
+                return res;
+            }
             if self.ctrl.is_terminated() || self.cfg.time_limit_hit(start) {
                 self.terminate_running();
                 let _ = lemma_mgr_join.map(|j| j.join());
                 if let Some(ui) = self.ui.as_ref() {
                     ui.finish(McResult::Unknown(None));
                 }
-                if interrupt.is_interrupted() {
-                    exit(130);
-                }
                 return McResult::Unknown(None);
             }
 
@@ -377,6 +584,7 @@ impl Engine for Portfolio {
             }
 
             self.poll_state_traces();
+            self.enforce_mem_limit();
 
             if let Some(res) = self.reap_child() {
                 self.terminate_running();
@@ -397,6 +605,17 @@ impl Engine for Portfolio {
         self.ctrl.clone()
     }
 
+    fn report(&mut self, report: &mut Report) {
+        report.preproc_ts = Some(TsStatistic::new(&self.ts));
+        report.workers = self
+            .engines
+            .iter()
+            .map(|w| WorkerReport::new(&w.name, &w.args, w.state, w.mem as u64, w.memout))
+            .collect();
+        report.worker = self.winner_idx.map(|w| report.workers[w].clone());
+        report.statistic.extend(self.statistic.clone());
+    }
+
     fn set_ui(&mut self, renderer: UiRenderer) {
         self.ui = Some(PortfolioUi::new(
             renderer,
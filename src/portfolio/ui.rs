@@ -1,4 +1,7 @@
-use crate::{McResult, ui::UiRenderer};
+use crate::{
+    McResult,
+    ui::{UiRenderer, format_kib},
+};
 use ratatui::{
     style::{Color, Style, Stylize},
     text::{Line, Span},
@@ -6,6 +9,7 @@ use ratatui::{
 
 const ID_WIDTH: usize = 4;
 const BOUND_WIDTH: usize = 8;
+const MEM_WIDTH: usize = 10;
 const DEFAULT_WORKER_WIDTH: usize = 24;
 
 pub(super) struct PortfolioUi {
@@ -16,6 +20,8 @@ pub(super) struct PortfolioUi {
 struct WorkerUiState {
     name: String,
     res: McResult,
+    mem: Option<usize>,
+    memout: bool,
 }
 
 impl PortfolioUi {
@@ -27,6 +33,8 @@ impl PortfolioUi {
                 .map(|name| WorkerUiState {
                     name,
                     res: McResult::Unknown(None),
+                    mem: None,
+                    memout: false,
                 })
                 .collect(),
         };
@@ -41,6 +49,20 @@ impl PortfolioUi {
         self.render();
     }
 
+    pub(super) fn update_mem(&mut self, worker_idx: usize, mem: usize) {
+        if let Some(worker) = self.workers.get_mut(worker_idx) {
+            worker.mem = Some(mem);
+        }
+        self.render();
+    }
+
+    pub(super) fn memout(&mut self, worker_idx: usize) {
+        if let Some(worker) = self.workers.get_mut(worker_idx) {
+            worker.memout = true;
+        }
+        self.render();
+    }
+
     pub(super) fn finish(&self, res: McResult) {
         self.renderer.finish(res);
     }
@@ -66,11 +88,14 @@ fn header_line() -> Line<'static> {
         Span::raw(format!("{:<DEFAULT_WORKER_WIDTH$}", "Worker")).bold(),
         Span::raw(" "),
         Span::raw(format!("{:<BOUND_WIDTH$}", "Bound")).bold(),
+        Span::raw(" "),
+        Span::raw(format!("{:<MEM_WIDTH$}", "Mem")).bold(),
     ])
 }
 
 fn worker_line(idx: usize, worker: &WorkerUiState) -> Line<'static> {
     let (bound, bound_style) = bound_column(worker.res);
+    let (mem, mem_style) = mem_column(worker);
     Line::from(vec![
         Span::raw("  "),
         Span::raw(format!("{:<ID_WIDTH$}", idx)).style(Style::default().fg(Color::Magenta).bold()),
@@ -79,9 +104,24 @@ fn worker_line(idx: usize, worker: &WorkerUiState) -> Line<'static> {
             .style(Style::default().fg(Color::White)),
         Span::raw(" "),
         Span::raw(format!("{bound:<BOUND_WIDTH$}")).style(bound_style),
+        Span::raw(" "),
+        Span::raw(format!("{mem:<MEM_WIDTH$}")).style(mem_style),
     ])
 }
 
+fn mem_column(worker: &WorkerUiState) -> (String, Style) {
+    if worker.memout {
+        return ("memout".to_string(), Style::default().fg(Color::Red).bold());
+    }
+    match worker.mem {
+        Some(mem) => (
+            format_kib((mem / 1024) as u64),
+            Style::default().fg(Color::White),
+        ),
+        None => ("-".to_string(), Style::default().fg(Color::DarkGray)),
+    }
+}
+
 fn bound_column(res: McResult) -> (String, Style) {
     match res {
         McResult::UNSAT => (
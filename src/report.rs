@@ -0,0 +1,175 @@
+use crate::{McResult, transys::TransysIf};
+use anyhow::Context;
+use nix::sys::resource::{UsageWho, getrusage};
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path, path::PathBuf, time::Instant};
+
+/// Size of a transition system.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct TsStatistic {
+    pub vars: usize,
+    pub inputs: usize,
+    pub latches: usize,
+    pub clauses: usize,
+    pub constraints: usize,
+}
+
+impl TsStatistic {
+    pub fn new(ts: &impl TransysIf) -> Self {
+        Self {
+            vars: Into::<usize>::into(ts.max_var()),
+            inputs: ts.input().count(),
+            latches: ts.latch().count(),
+            clauses: ts.trans().count(),
+            constraints: ts.constraint().count(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PropReport {
+    pub id: usize,
+    pub name: Option<String>,
+    /// SAT, UNSAT or UNKNOWN
+    pub result: String,
+    /// cex length of SAT, or the bound reached by UNKNOWN
+    pub depth: Option<usize>,
+    pub certificate: Option<PathBuf>,
+    /// assumptions the proof depends on, checked by `--vacuity`
+    pub assumptions: Option<Vec<String>>,
+}
+
+/// The result name and the depth of a SAT or UNKNOWN result.
+fn result_depth(res: McResult) -> (&'static str, Option<usize>) {
+    match res {
+        McResult::UNSAT => ("UNSAT", None),
+        McResult::SAT(d) => ("SAT", Some(d)),
+        McResult::Unknown(d) => ("UNKNOWN", d),
+    }
+}
+
+impl PropReport {
+    pub fn new(id: usize, name: Option<String>, res: McResult) -> Self {
+        let (result, depth) = result_depth(res);
+        Self {
+            id,
+            name,
+            result: result.to_string(),
+            depth,
+            certificate: None,
+            assumptions: None,
+        }
+    }
+}
+
+/// Outcome of a portfolio worker.
+#[derive(Serialize, Debug, Clone)]
+pub struct WorkerReport {
+    pub name: String,
+    pub args: String,
+    /// SAT, UNSAT or UNKNOWN
+    pub result: String,
+    /// cex length of SAT, or the bound reached by UNKNOWN
+    pub depth: Option<usize>,
+    /// resident memory in bytes, as last reported
+    pub memory: u64,
+    pub memout: bool,
+}
+
+impl WorkerReport {
+    pub fn new(name: &str, args: &str, res: McResult, memory: u64, memout: bool) -> Self {
+        let (result, depth) = result_depth(res);
+        Self {
+            name: name.to_string(),
+            args: args.to_string(),
+            result: result.to_string(),
+            depth,
+            memory,
+            memout,
+        }
+    }
+}
+
+/// Satisfiability of the assumptions, checked by `--vacuity`.
+#[derive(Serialize, Debug, Clone)]
+pub struct VacuityReport {
+    /// number of assumptions
+    pub assumptions: usize,
+    /// depth up to which the assumptions are checked by BMC
+    pub depth: usize,
+    /// first depth at which no path satisfies the assumptions
+    pub conflict: Option<usize>,
+}
+
+/// Machine-readable report of a verification run, written by `--report`.
+#[derive(Serialize, Debug, Default)]
+pub struct Report {
+    pub model: Option<PathBuf>,
+    pub engine: String,
+    /// the winning portfolio worker
+    pub worker: Option<WorkerReport>,
+    /// every portfolio worker
+    pub workers: Vec<WorkerReport>,
+    pub result: Option<String>,
+    pub properties: Vec<PropReport>,
+    /// cover points of `ric3 run`, SAT when covered
+    pub covers: Vec<PropReport>,
+    /// the model before preprocessing
+    pub ts: Option<TsStatistic>,
+    /// the model after preprocessing
+    pub preproc_ts: Option<TsStatistic>,
+    pub certificate: Option<PathBuf>,
+    pub vacuity: Option<VacuityReport>,
+    /// wall time in seconds
+    pub wall_time: f64,
+    /// cpu time of this process and its workers in seconds
+    pub cpu_time: f64,
+    /// peak resident memory of this process or a worker in bytes
+    pub peak_memory: u64,
+    /// engine statistics, by name
+    pub statistic: BTreeMap<String, Value>,
+    #[serde(skip)]
+    start: Option<Instant>,
+}
+
+impl Report {
+    pub fn new(engine: &str) -> Self {
+        Self {
+            engine: engine.to_string(),
+            start: Some(Instant::now()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the result of a single property check.
+    pub fn set_result(&mut self, res: McResult) {
+        let mut prop = PropReport::new(0, None, res);
+        if !res.is_unknown() {
+            prop.certificate = self.certificate.clone();
+        }
+        self.result = Some(prop.result.clone());
+        self.properties = vec![prop];
+    }
+
+    /// Records time and memory of the run.
+    pub fn finish(&mut self) {
+        if let Some(start) = self.start {
+            self.wall_time = start.elapsed().as_secs_f64();
+        }
+        for who in [UsageWho::RUSAGE_SELF, UsageWho::RUSAGE_CHILDREN] {
+            let Ok(usage) = getrusage(who) else {
+                continue;
+            };
+            let cpu = usage.user_time() + usage.system_time();
+            self.cpu_time += cpu.tv_sec() as f64 + cpu.tv_usec() as f64 / 1e6;
+            // max rss is in KiB on Linux
+            self.peak_memory = self.peak_memory.max(usage.max_rss() as u64 * 1024);
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).with_context(|| format!("failed to write report: {}", path.display()))
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     transys::{Transys, TransysIf, unroll::TransysUnroll},
 };
 use giputils::hash::GHashMap;
-use logicrs::{Lit, LitVec, LitVvec, Var, VarVMap, satif::Satif};
+use logicrs::{DagCnf, Lit, LitVec, LitVvec, Var, VarRange, VarVMap, satif::Satif};
 use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
 
@@ -106,7 +106,12 @@ impl BlCex {
         (self.input[0], self.state[0]) = (input, state);
     }
 
-    pub fn exact_state(&mut self, ts: &Transys, init: bool) {
+    /// Makes the cex exact on the given ts. The last `enlarge` steps, left
+    /// empty by target enlargement, are completed by SAT, and the cex ends at
+    /// the first of them that reaches a bad state. Fails, leaving the cex
+    /// unchanged, if no bad state is reached.
+    pub fn exact_state(&mut self, ts: &Transys, init: bool, enlarge: usize) -> anyhow::Result<()> {
+        let base = (self.len() - 1).saturating_sub(enlarge);
         let mut uts = TransysUnroll::new(ts);
         uts.unroll_to(self.len() - 1);
         let mut solver = cadical::CaDiCaL::new();
@@ -122,18 +127,47 @@ impl BlCex {
             {
                 solver.add_clause(&[l]);
             }
+            if k < base {
+                continue;
+            }
+            // the recorded bad is preferred, so a cex of one property keeps
+            // its bad id when other bads are reachable at the same step
+            if let Some(b) = [self.bad_id]
+                .into_iter()
+                .filter(|&b| b < ts.bad.len())
+                .chain(0..ts.bad.len())
+                .find(|&b| solver.solve(&[uts.lit_next(ts.bad[b], k)]))
+            {
+                *self = uts.cex(&solver);
+                self.input.truncate(k + 1);
+                self.state.truncate(k + 1);
+                self.bad_id = b;
+                return Ok(());
+            }
         }
-        assert!(solver.solve(&[]));
-        *self = uts.cex(&solver);
-        self.bad_id = ts
-            .bad
-            .iter()
-            .position(|&b| {
-                solver
-                    .sat_value(uts.lit_next(b, uts.num_unroll))
-                    .is_some_and(|v| v)
-            })
-            .unwrap();
+        anyhow::bail!("cex does not reach a bad state")
+    }
+
+    /// Replays the cex on `ts` with its recorded values, which may be partial.
+    /// Returns the exact cex when bad `prop` is reached at its last step.
+    pub fn replay(&self, ts: &Transys, prop: usize) -> Option<BlCex> {
+        let k = self.len().checked_sub(1)?;
+        let mut uts = TransysUnroll::new(ts);
+        uts.unroll_to(k);
+        let mut solver = cadical::CaDiCaL::new();
+        ts.load_init(&mut solver);
+        for u in 0..=k {
+            uts.load_trans(&mut solver, u, true);
+            for l in self.state[u].iter().chain(self.input[u].iter()) {
+                solver.add_clause(&[uts.lit_next(*l, u)]);
+            }
+        }
+        if !solver.solve(&[uts.lit_next(ts.bad[prop], k)]) {
+            return None;
+        }
+        let mut cex = uts.cex(&solver);
+        cex.bad_id = prop;
+        Some(cex)
     }
 
     pub fn lift(&mut self, ts: &Transys, additional_target: Option<impl Fn(usize) -> LitVec>) {
@@ -158,6 +192,45 @@ impl BlCex {
     }
 }
 
+impl Transys {
+    /// Whether the bads are excluded by 1-induction: no bad holds in an
+    /// initial state, and none is reached in one step from a state without
+    /// bad.
+    pub fn is_inductive(&self) -> bool {
+        let mut uts = TransysUnroll::new(self);
+        uts.unroll_to(1);
+        let mut base = cadical::CaDiCaL::new();
+        self.load_init(&mut base);
+        uts.load_trans(&mut base, 0, true);
+        if self.bad.iter().any(|&b| base.solve(&[b])) {
+            return false;
+        }
+        let mut step = cadical::CaDiCaL::new();
+        uts.load_trans(&mut step, 0, true);
+        uts.load_trans(&mut step, 1, true);
+        for &b in self.bad.iter() {
+            step.add_clause(&[!b]);
+        }
+        !self.bad.iter().any(|&b| step.solve(&[uts.lit_next(b, 1)]))
+    }
+
+    /// The first depth up to `max_depth` at which no path from an initial
+    /// state satisfies the constraints, found by BMC.
+    pub fn constraint_conflict(&self, max_depth: usize) -> Option<usize> {
+        let mut uts = TransysUnroll::new(self);
+        let mut solver = cadical::CaDiCaL::new();
+        self.load_init(&mut solver);
+        for k in 0..=max_depth {
+            uts.unroll_to(k);
+            uts.load_trans(&mut solver, k, true);
+            if !solver.solve(&[]) {
+                return Some(k);
+            }
+        }
+        None
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct BlProof {
     pub proof: Transys,
@@ -188,6 +261,208 @@ impl BlProof {
         self.proof
             .merge(&other.proof, |v| (v <= ts.max_var()).then_some(v));
     }
+
+    /// Witness of a property of `ts` that is `k`-inductive. The witness keeps
+    /// the last `k` steps in shadow latches, which makes the property
+    /// 1-inductive.
+    pub fn k_induction(mut ts: Transys, k: usize) -> Self {
+        if ts.bad.len() > 1 {
+            ts.bad = LitVec::from([ts.rel.new_or(ts.bad)]);
+        }
+        if ts.constraint.len() > 1 {
+            ts.constraint = LitVec::from([ts.rel.new_and(ts.constraint)]);
+        }
+        let mut proof = ts.clone();
+        let ni = proof.input.len();
+        let nl = proof.latch.len();
+        let mut inputs = proof.input.clone();
+        let mut latchs = proof.latch.clone();
+        let mut next = proof.next.clone();
+        let mut inits = proof.init.clone();
+        let mut bads = proof.bad.clone();
+        let mut constrains = proof.constraint.clone();
+        for _ in 1..k {
+            let offset = proof.max_var();
+            let map = |x: Var| {
+                if x == Var::CONST { x } else { x + offset }
+            };
+            proof.new_var_to(map(ts.max_var()));
+            let lmap = |x: Lit| Lit::new(map(x.var()), x.polarity());
+            for v in VarRange::new_inclusive(Var(1), ts.max_var()) {
+                let rel: Vec<LitVec> = ts.rel[v].iter().map(|cls| cls.map(lmap)).collect();
+                let mv = map(v);
+                proof.rel.add_rel(mv, &rel);
+            }
+            for &i in ts.input.iter() {
+                inputs.push(map(i));
+            }
+            for &l in ts.latch.iter() {
+                let ml = map(l);
+                latchs.push(ml);
+                next.insert(ml, lmap(ts.next[&l]));
+                if let Some(i) = ts.init.get(&l) {
+                    inits.insert(ml, lmap(*i));
+                }
+            }
+            bads.extend(ts.bad.map(lmap));
+            for &l in ts.constraint.iter() {
+                constrains.push(lmap(l));
+            }
+        }
+        if !constrains.is_empty() {
+            for i in 0..k {
+                bads[i] = proof.rel.new_or([bads[i], !constrains[i]]);
+            }
+        }
+        let sum = inputs.len() + latchs.len();
+        let mut aux_latchs: Vec<Lit> = Vec::new();
+        for i in 0..k {
+            let aux = proof.new_var().lit();
+            aux_latchs.push(aux);
+            let (next, init) = if i == 0 {
+                (aux, Some(Lit::constant(true)))
+            } else {
+                (aux_latchs[i - 1], Some(Lit::constant(false)))
+            };
+            proof.add_latch(aux.var(), init, next);
+        }
+        for i in 1..k {
+            for j in 0..ni {
+                proof.add_latch(inputs[j + i * ni], None, inputs[j + (i - 1) * ni].lit());
+            }
+            for j in 0..nl {
+                proof.add_latch(latchs[j + i * nl], None, latchs[j + (i - 1) * nl].lit());
+            }
+        }
+        for i in 0..k {
+            let al = aux_latchs[i];
+            let p = proof.rel.new_imply(al, !bads[i]);
+            bads[i] = !p;
+        }
+
+        for i in 1..k {
+            let al = aux_latchs[i];
+            let al_next = aux_latchs[i - 1];
+            let p = proof.rel.new_imply(al, al_next);
+            bads.push(!p);
+            let mut eqs = Vec::new();
+            let mut init = Vec::new();
+            for j in 0..nl {
+                let lis1j = latchs[(i - 1) * nl + j];
+                if let Some(&linit) = inits.get(&lis1j) {
+                    init.push(LitVec::from([lis1j.lit(), !linit]));
+                    init.push(LitVec::from([!lis1j.lit(), linit]));
+                }
+                eqs.push(
+                    proof
+                        .rel
+                        .new_xnor(next[&latchs[j + i * nl]], latchs[j + (i - 1) * nl].lit()),
+                );
+            }
+            let p = proof.rel.new_and(eqs);
+            let p = proof.rel.new_imply(al, p);
+            bads.push(!p);
+            let init: Vec<_> = init.into_iter().map(|cls| proof.rel.new_or(cls)).collect();
+            let init = proof.rel.new_and(init);
+            let p = proof.rel.new_and([!al, al_next]);
+            let p = proof.rel.new_imply(p, init);
+            bads.push(!p);
+        }
+        bads.push(!aux_latchs[0]);
+        proof.bad = LitVec::from(proof.rel.new_or(bads));
+        assert_eq!(proof.input.len() + proof.latch.len(), sum + k);
+        Self { proof }
+    }
+}
+
+/// The value of a signal that takes `values` in the first steps and keeps the
+/// last one afterwards, over time latches where the `i`-th latch is set from
+/// step `i + 1` on.
+pub(crate) fn time_mux(rel: &mut DagCnf, time: &[Var], values: &[bool]) -> Lit {
+    let (&c, prefix) = values.split_last().unwrap();
+    let mut res = Lit::constant(c);
+    for (&t, &v) in time.iter().zip(prefix).rev() {
+        res = if v {
+            rel.new_or([!t.lit(), res])
+        } else {
+            rel.new_and([t.lit(), res])
+        };
+    }
+    res
+}
+
+/// Time latches added by ternary simulation and the transient signals over
+/// them, in virtual vars.
+#[derive(Debug, Clone, Default)]
+pub struct TimeRestore {
+    /// the `i`-th latch is set from step `i + 1` on
+    pub(crate) time: Vec<Var>,
+    /// signals with their values in the first steps
+    pub(crate) transient: Vec<(Var, Vec<bool>)>,
+}
+
+impl TimeRestore {
+    fn contains(&self, v: Var) -> bool {
+        self.time.contains(&v) || self.transient.iter().any(|(t, _)| *t == v)
+    }
+
+    /// Defines the time latches and the transient signals in `proof`.
+    fn add_to(&self, proof: &mut Transys) {
+        for (i, &t) in self.time.iter().enumerate() {
+            let next = if i == 0 {
+                Lit::constant(true)
+            } else {
+                self.time[i - 1].lit()
+            };
+            proof.add_latch(t, Some(Lit::constant(false)), next);
+        }
+        for (v, values) in self.transient.iter() {
+            let m = time_mux(&mut proof.rel, &self.time, values);
+            let rel = [LitVec::from([v.lit(), !m]), LitVec::from([!v.lit(), m])];
+            proof.rel.add_rel(*v, &rel);
+        }
+    }
+}
+
+/// Inverse of an input reparameterization, in original vars.
+#[derive(Debug, Clone, Default)]
+pub struct ReparamRestore {
+    /// relation of both the old and the new cut points
+    pub(crate) rel: DagCnf,
+    /// replaced inputs in rel, with their original vars
+    pub(crate) input: Vec<(Var, Var)>,
+    /// fresh inputs in rel, with their virtual vars
+    pub(crate) fresh: Vec<(Var, Var)>,
+    /// old cut points with their new cut points
+    pub(crate) cut: Vec<(Lit, Lit)>,
+}
+
+impl ReparamRestore {
+    /// Replaces the fresh inputs of each step with values of the replaced
+    /// inputs that produce the same cut point values.
+    fn restore(&self, cex: &mut BlCex) {
+        let virt: GHashMap<Var, Var> = self.fresh.iter().map(|&(f, v)| (v, f)).collect();
+        let mut slv = DagCnfSolver::new(&self.rel);
+        for input in cex.input.iter_mut() {
+            let assump: LitVec = input
+                .iter()
+                .filter_map(|l| virt.get(&l.var()).map(|&f| Lit::new(f, l.polarity())))
+                .collect();
+            assert!(slv.solve(&assump));
+            let cut: LitVec = self
+                .cut
+                .iter()
+                .map(|&(c, n)| c.not_if(!slv.sat_value(n).unwrap()))
+                .collect();
+            assert!(slv.solve(&cut));
+            input.retain(|l| !virt.contains_key(&l.var()));
+            for &(i, o) in self.input.iter() {
+                if let Some(v) = slv.sat_value(i.lit()) {
+                    input.push(o.lit().not_if(!v));
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -196,6 +471,19 @@ pub struct Restore {
     pub(crate) fvmap: VarVMap,
     eqmap: GHashMap<Var, LitVec>,
     init_var: Option<Var>,
+    /// initial values of the latches absorbed by retiming, in original vars
+    init_state: LitVec,
+    /// time latches of ternary simulation
+    time: Vec<TimeRestore>,
+    /// input reparameterizations in applied order
+    reparam: Vec<ReparamRestore>,
+    /// number of steps the target is enlarged by
+    enlarge: usize,
+    /// induction depth of the equivalences in eqmap
+    eq_depth: usize,
+    /// largest var used as an original var, vars beyond the original model
+    /// are virtual
+    max_var: Var,
 }
 
 impl Restore {
@@ -205,6 +493,12 @@ impl Restore {
             fvmap: VarVMap::new_self_map(ts.max_var()),
             eqmap: GHashMap::default(),
             init_var: None,
+            init_state: LitVec::new(),
+            time: Vec::new(),
+            reparam: Vec::new(),
+            enlarge: 0,
+            eq_depth: 1,
+            max_var: ts.max_var(),
         }
     }
 
@@ -297,6 +591,17 @@ impl Restore {
         res
     }
 
+    /// Records that the equivalences are only `k`-inductive.
+    #[inline]
+    pub fn set_eq_depth(&mut self, k: usize) {
+        self.eq_depth = self.eq_depth.max(k);
+    }
+
+    #[inline]
+    pub fn eq_depth(&self) -> usize {
+        self.eq_depth
+    }
+
     pub fn init_var(&self) -> Option<Var> {
         self.init_var
     }
@@ -310,6 +615,61 @@ impl Restore {
         iv
     }
 
+    #[inline]
+    pub fn add_init_state(&mut self, l: Lit) {
+        self.init_state.push(l);
+    }
+
+    #[inline]
+    pub fn is_retimed(&self) -> bool {
+        !self.init_state.is_empty()
+    }
+
+    #[inline]
+    pub fn add_time(&mut self, t: TimeRestore) {
+        self.time.push(t);
+    }
+
+    /// Whether `v` is a virtual var of a time latch or a transient signal.
+    #[inline]
+    pub fn is_time(&self, v: Var) -> bool {
+        self.time.iter().any(|t| t.contains(v))
+    }
+
+    /// Adds the time latches and the transient signals to `proof` at their
+    /// virtual vars, which the restored invariants refer to.
+    pub fn add_time_witness(&self, proof: &mut Transys) {
+        if self.time.is_empty() {
+            return;
+        }
+        proof.new_var_to(self.max_var);
+        for t in self.time.iter() {
+            t.add_to(proof);
+        }
+    }
+
+    /// New virtual var for a var without an original counterpart.
+    #[inline]
+    pub fn new_virtual_var(&mut self) -> Var {
+        self.max_var += 1;
+        self.max_var
+    }
+
+    #[inline]
+    pub fn add_reparam(&mut self, r: ReparamRestore) {
+        self.reparam.push(r);
+    }
+
+    #[inline]
+    pub fn add_enlarge(&mut self, k: usize) {
+        self.enlarge += k;
+    }
+
+    #[inline]
+    pub fn enlarge(&self) -> usize {
+        self.enlarge
+    }
+
     pub fn restore_eq_state(&self, s: &LitVec) -> LitVec {
         let mut res = s.clone();
         for l in s.iter() {
@@ -330,22 +690,55 @@ impl Restore {
         for s in cex.state.iter_mut() {
             *s = self.restore_eq_state(s);
         }
+        if let Some(s) = cex.state.first_mut() {
+            s.extend(self.init_state.iter().copied());
+            s.sort();
+            s.dedup();
+        }
+        if !self.time.is_empty() {
+            for s in cex.state.iter_mut() {
+                s.retain(|l| !self.is_time(l.var()));
+            }
+        }
+        for r in self.reparam.iter().rev() {
+            r.restore(&mut cex);
+        }
+        // the enlarged target is completed by exact_state
+        for _ in 0..self.enlarge {
+            cex.input.push(LitVec::new());
+            cex.state.push(LitVec::new());
+        }
         cex
     }
 
     pub fn restore_proof(&self, mut proof: BlProof, ts: &Transys) -> BlProof {
         let mut res = ts.clone();
+        self.add_time_witness(&mut res);
         proof.constraint.clear();
-        res.merge(&proof, |v| self.bvmap.get(&v).copied());
+        res.merge(&proof, |v| {
+            self.bvmap
+                .get(&v)
+                .copied()
+                .filter(|m| *m <= ts.max_var() || self.is_time(*m))
+        });
         let eqi = self.eq_invariant();
         for cube in eqi {
             res.bad.push(res.rel.new_and(cube));
         }
+        if self.eq_depth > 1 {
+            return BlProof::k_induction(res, self.eq_depth);
+        }
         BlProof { proof: res }
     }
 
     pub fn forward_cex(&self, cex: &BlCex) -> BlCex {
-        assert!(self.eqmap.is_empty());
+        assert!(
+            self.eqmap.is_empty()
+                && self.init_state.is_empty()
+                && self.time.is_empty()
+                && self.reparam.is_empty()
+                && self.enlarge == 0
+        );
         let mut res = cex.clone();
         for k in 0..res.len() {
             res.input[k] = res.input[k]
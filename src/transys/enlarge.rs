@@ -0,0 +1,69 @@
+use crate::{
+    config::PreprocConfig,
+    transys::{Transys, TransysIf, certify::Restore, unroll::TransysUnroll},
+};
+use log::info;
+use logicrs::LitVec;
+use std::time::Instant;
+
+/// Target enlargement. Each bad is replaced by the states that reach it within
+/// k steps, computed by unrolling the transition relation with fresh inputs
+/// and simplifying the result structurally. A cex of the enlarged target is
+/// completed with the remaining steps when restored.
+pub struct Enlarge {
+    ts: Transys,
+    rst: Restore,
+    k: usize,
+}
+
+impl Enlarge {
+    pub fn new(ts: Transys, cfg: &PreprocConfig, rst: Restore) -> Self {
+        Self {
+            ts,
+            rst,
+            k: cfg.enlarge_k,
+        }
+    }
+
+    pub fn enlarge(mut self) -> (Transys, Restore) {
+        let start = Instant::now();
+        if self.k == 0 || self.ts.bad.is_empty() || !self.ts.justice.is_empty() {
+            return (self.ts, self.rst);
+        }
+        let ots = self.ts.clone();
+        let mut uts = TransysUnroll::new(&ots);
+        uts.unroll_to(self.k);
+        self.ts.new_var_to(uts.max_var);
+        let mut bad: Vec<LitVec> = ots.bad.iter().map(|&b| LitVec::from([b])).collect();
+        let mut constraint = LitVec::new();
+        for u in 1..=self.k {
+            for i in ots.input.iter() {
+                self.ts.input.push(uts.var_next(*i, u));
+            }
+            for (v, cls) in ots.rel.iter() {
+                let v = uts.var_next(v, u);
+                if self.ts.rel.has_rel(v) {
+                    continue;
+                }
+                let cls: Vec<LitVec> = cls.iter().map(|c| uts.lits_next(c, u).collect()).collect();
+                self.ts.rel.add_rel(v, &cls);
+            }
+            constraint.extend(uts.lits_next(&ots.constraint, u));
+            for (b, &ob) in bad.iter_mut().zip(ots.bad.iter()) {
+                let mut reach = constraint.clone();
+                reach.push(uts.lit_next(ob, u));
+                b.push(self.ts.rel.new_and(reach));
+            }
+        }
+        self.ts.bad = bad.into_iter().map(|b| self.ts.rel.new_or(b)).collect();
+        self.rst.add_enlarge(self.k);
+        self.ts.simplify(&mut self.rst);
+        info!(
+            "enlarge: enlarges target by {} steps in {:.2}s",
+            self.k,
+            start.elapsed().as_secs_f32()
+        );
+        info!("enlarge: simplified ts: {}", self.ts.statistic());
+        (self.ts, self.rst)
+    }
+}
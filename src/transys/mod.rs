@@ -1,15 +1,20 @@
 mod aux;
 pub mod certify;
 mod ctx;
+pub mod enlarge;
 pub mod frts;
 pub mod lift;
 mod live;
 pub mod nodep;
 mod others;
 mod refactor;
+pub mod reparam;
+pub mod retime;
 pub mod scorr;
 mod simp;
 mod simulate;
+mod test;
+pub mod tsim;
 pub mod unroll;
 
 pub use ctx::*;
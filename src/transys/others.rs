@@ -27,6 +27,32 @@ impl Transys {
         frozens
     }
 
+    /// Copies the relation of `v` with its dependencies substituted by `map`,
+    /// returns the new variable.
+    pub fn copy_rel(&mut self, v: Var, map: impl Fn(Lit) -> Lit) -> Var {
+        let n = self.rel.new_var();
+        let mut rel = Vec::new();
+        'c: for cls in self.rel[v].iter() {
+            let mut ncls = LitVec::new();
+            for &l in cls.iter() {
+                let m = if l.var() == v {
+                    Lit::new(n, l.polarity())
+                } else {
+                    map(l)
+                };
+                if ncls.contains(&!m) {
+                    continue 'c;
+                }
+                if !ncls.contains(&m) {
+                    ncls.push(m);
+                }
+            }
+            rel.push(ncls);
+        }
+        self.rel.add_rel(n, &rel);
+        n
+    }
+
     pub fn merge(&mut self, other: &Self, mapf: impl Fn(Var) -> Option<Var>) {
         let begin = self.max_var();
         let mut vmap = GHashMap::new();
@@ -0,0 +1,238 @@
+use crate::{
+    config::PreprocConfig,
+    gipsat::DagCnfSolver,
+    transys::{
+        Transys, TransysIf,
+        certify::{ReparamRestore, Restore},
+    },
+};
+use giputils::hash::{GHashMap, GHashSet};
+use log::{debug, info};
+use logicrs::{Lit, LitVec, Var, VarLMap, satif::Satif};
+use std::time::Instant;
+
+/// Input reparameterization. Regions of the logic that only depend on inputs
+/// are split into components of cut points with disjoint input supports. A
+/// component with fewer cut points than inputs is replaced by fresh inputs,
+/// one per cut point, driving a circuit with the same range as the cut points.
+pub struct Reparam {
+    ts: Transys,
+    rst: Restore,
+    max_cut: usize,
+}
+
+impl Reparam {
+    pub fn new(mut ts: Transys, cfg: &PreprocConfig, mut rst: Restore) -> Self {
+        ts.topsort(&mut rst);
+        Self {
+            ts,
+            rst,
+            max_cut: cfg.reparam_cut,
+        }
+    }
+
+    /// Inputs and the gates that only depend on inputs.
+    fn region(&self) -> GHashSet<Var> {
+        let mut region: GHashSet<Var> = self
+            .ts
+            .input
+            .iter()
+            .copied()
+            .filter(|i| self.ts.init(*i).is_none())
+            .collect();
+        for v in self.ts.rel.var_iter() {
+            if v.is_constant() || self.ts.rel.is_leaf(v) {
+                continue;
+            }
+            let dep = self.ts.rel.dep(v);
+            if dep.iter().any(|d| region.contains(d))
+                && dep.iter().all(|d| d.is_constant() || region.contains(d))
+            {
+                region.insert(v);
+            }
+        }
+        region
+    }
+
+    fn cone(&self, roots: impl IntoIterator<Item = Var>) -> GHashSet<Var> {
+        let mut cone = GHashSet::new();
+        let mut queue: Vec<_> = roots.into_iter().collect();
+        while let Some(v) = queue.pop() {
+            if cone.insert(v) {
+                queue.extend(self.ts.rel.dep(v).iter().copied());
+            }
+        }
+        cone
+    }
+
+    /// Cut points of the region grouped by input support, as (inputs, cut
+    /// points). Components in the cone of constraints are left out.
+    fn components(&self, region: &GHashSet<Var>) -> Vec<(Vec<Var>, Vec<Var>)> {
+        let mut external: GHashSet<Var> = self
+            .ts
+            .bad
+            .iter()
+            .chain(self.ts.next.values())
+            .chain(self.ts.init.values())
+            .map(|l| l.var())
+            .collect();
+        for v in self.ts.rel.var_iter() {
+            if !self.ts.rel.is_leaf(v) && !region.contains(&v) {
+                external.extend(self.ts.rel.dep(v).iter().copied());
+            }
+        }
+        let cst_cone = self.cone(self.ts.constraint.iter().map(|l| l.var()));
+        let mut cut: Vec<_> = region
+            .iter()
+            .copied()
+            .filter(|v| external.contains(v))
+            .collect();
+        cut.sort();
+        let mut parent: GHashMap<Var, Var> = GHashMap::new();
+        fn find(parent: &mut GHashMap<Var, Var>, v: Var) -> Var {
+            let p = *parent.entry(v).or_insert(v);
+            if p == v {
+                return v;
+            }
+            let r = find(parent, p);
+            parent.insert(v, r);
+            r
+        }
+        let mut support = Vec::new();
+        for &c in cut.iter() {
+            let s: Vec<_> = self
+                .cone([c])
+                .into_iter()
+                .filter(|v| self.ts.rel.is_leaf(*v) && region.contains(v))
+                .collect();
+            for &i in s.iter().skip(1) {
+                let (x, y) = (find(&mut parent, s[0]), find(&mut parent, i));
+                parent.insert(x, y);
+            }
+            support.push(s);
+        }
+        let mut comp: GHashMap<Var, (Vec<Var>, Vec<Var>)> = GHashMap::new();
+        for (&c, s) in cut.iter().zip(support) {
+            let r = find(&mut parent, s[0]);
+            let e = comp.entry(r).or_default();
+            e.0.extend(s);
+            e.1.push(c);
+        }
+        let mut res = Vec::new();
+        for (_, (mut input, cut)) in comp {
+            input.sort();
+            input.dedup();
+            if cut.len() < input.len()
+                && cut.len() <= self.max_cut
+                && input
+                    .iter()
+                    .all(|i| !cst_cone.contains(i) && self.rst.try_restore(i.lit()).is_some())
+            {
+                res.push((input, cut));
+            }
+        }
+        res.sort();
+        res
+    }
+
+    /// All values the cut points can take.
+    fn range(&self, cut: &[Var]) -> Vec<Vec<bool>> {
+        let mut slv = DagCnfSolver::new(&self.ts.rel);
+        let mut range = Vec::new();
+        while slv.solve_with_domain(&[], cut.iter().copied()) {
+            let r: Vec<_> = cut
+                .iter()
+                .map(|c| slv.sat_value(c.lit()).unwrap())
+                .collect();
+            let block: LitVec = cut
+                .iter()
+                .zip(r.iter())
+                .map(|(c, &v)| c.lit().not_if(v))
+                .collect();
+            slv.add_clause(&block);
+            range.push(r);
+        }
+        range
+    }
+
+    /// Builds the new cut values over fresh inputs: a fresh assignment in the
+    /// range is passed through, any other is mapped to the first value of the
+    /// range.
+    fn new_cut(&mut self, fresh: &[Var], range: &[Vec<bool>]) -> Vec<Lit> {
+        if range.len() == 1 << fresh.len() {
+            return fresh.iter().map(|f| f.lit()).collect();
+        }
+        let terms: Vec<_> = range
+            .iter()
+            .map(|r| {
+                let cube: LitVec = fresh
+                    .iter()
+                    .zip(r.iter())
+                    .map(|(f, &v)| f.lit().not_if(!v))
+                    .collect();
+                self.ts.rel.new_and(cube)
+            })
+            .collect();
+        let in_range = self.ts.rel.new_or(terms);
+        fresh
+            .iter()
+            .zip(range[0].iter())
+            .map(|(f, &r)| {
+                if r {
+                    self.ts.rel.new_or([!in_range, f.lit()])
+                } else {
+                    self.ts.rel.new_and(LitVec::from([in_range, f.lit()]))
+                }
+            })
+            .collect()
+    }
+
+    pub fn reparam(mut self) -> (Transys, Restore) {
+        let start = Instant::now();
+        let region = self.region();
+        let comps = self.components(&region);
+        if comps.is_empty() {
+            info!("reparam: no input component to reparameterize");
+            return (self.ts, self.rst);
+        }
+        let mut rr = ReparamRestore::default();
+        let mut map = VarLMap::new();
+        let mut ninput = 0;
+        for (input, cut) in comps {
+            let range = self.range(&cut);
+            debug!(
+                "reparam: {} inputs with {} cut points of range {}",
+                input.len(),
+                cut.len(),
+                range.len()
+            );
+            let fresh: Vec<_> = cut.iter().map(|_| self.ts.rel.new_var()).collect();
+            let ncut = self.new_cut(&fresh, &range);
+            for &f in fresh.iter() {
+                let vv = self.rst.new_virtual_var();
+                self.rst.add_restore(f, vv);
+                rr.fresh.push((f, vv));
+            }
+            for &i in input.iter() {
+                rr.input.push((i, self.rst.restore_var(i)));
+            }
+            for (&c, &n) in cut.iter().zip(ncut.iter()) {
+                rr.cut.push((c.lit(), n));
+                map.insert_lit(c.lit(), n);
+            }
+            ninput += input.len();
+            self.ts.input.extend(fresh);
+        }
+        rr.rel = self.ts.rel.clone();
+        self.rst.add_reparam(rr);
+        let nfresh = map.len();
+        self.ts.replace(&map, &mut self.rst);
+        self.ts.simplify(&mut self.rst);
+        info!(
+            "reparam: replaces {ninput} inputs with {nfresh} fresh inputs in {:.2}s",
+            start.elapsed().as_secs_f32()
+        );
+        info!("reparam: simplified ts: {}", self.ts.statistic());
+        (self.ts, self.rst)
+    }
+}
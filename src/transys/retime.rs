@@ -0,0 +1,336 @@
+use crate::{
+    config::PreprocConfig,
+    gipsat::DagCnfSolver,
+    transys::{Transys, TransysIf, certify::Restore},
+};
+use giputils::hash::{GHashMap, GHashSet};
+use log::{debug, info};
+use logicrs::{Lit, LitVec, Var, satif::Satif};
+use std::{collections::VecDeque, time::Instant};
+
+const INF: u32 = u32::MAX / 2;
+const SOURCE: usize = 0;
+const SINK: usize = 1;
+
+/// Unit node-capacity max-flow network (Dinic) used to find the minimum
+/// register cut.
+struct FlowNet {
+    head: Vec<usize>,
+    next: Vec<usize>,
+    to: Vec<usize>,
+    cap: Vec<u32>,
+    level: Vec<u32>,
+    iter: Vec<usize>,
+}
+
+impl FlowNet {
+    fn new(num_node: usize) -> Self {
+        Self {
+            head: vec![usize::MAX; num_node],
+            next: Vec::new(),
+            to: Vec::new(),
+            cap: Vec::new(),
+            level: vec![0; num_node],
+            iter: vec![0; num_node],
+        }
+    }
+
+    fn add_edge(&mut self, x: usize, y: usize, cap: u32) {
+        for (x, y, cap) in [(x, y, cap), (y, x, 0)] {
+            self.to.push(y);
+            self.cap.push(cap);
+            self.next.push(self.head[x]);
+            self.head[x] = self.to.len() - 1;
+        }
+    }
+
+    fn bfs(&mut self) -> bool {
+        self.level.fill(u32::MAX);
+        self.level[SOURCE] = 0;
+        let mut queue = VecDeque::from([SOURCE]);
+        while let Some(x) = queue.pop_front() {
+            let mut e = self.head[x];
+            while e != usize::MAX {
+                let y = self.to[e];
+                if self.cap[e] > 0 && self.level[y] == u32::MAX {
+                    self.level[y] = self.level[x] + 1;
+                    queue.push_back(y);
+                }
+                e = self.next[e];
+            }
+        }
+        self.level[SINK] != u32::MAX
+    }
+
+    fn augment(&mut self) -> u32 {
+        let mut path: Vec<usize> = Vec::new();
+        let mut x = SOURCE;
+        loop {
+            if x == SINK {
+                let f = path.iter().map(|&e| self.cap[e]).min().unwrap();
+                for &e in path.iter() {
+                    self.cap[e] -= f;
+                    self.cap[e ^ 1] += f;
+                }
+                return f;
+            }
+            let mut found = false;
+            while self.iter[x] != usize::MAX {
+                let e = self.iter[x];
+                let y = self.to[e];
+                if self.cap[e] > 0 && self.level[y] == self.level[x] + 1 {
+                    path.push(e);
+                    x = y;
+                    found = true;
+                    break;
+                }
+                self.iter[x] = self.next[e];
+            }
+            if !found {
+                if x == SOURCE {
+                    return 0;
+                }
+                self.level[x] = u32::MAX;
+                let e = path.pop().unwrap();
+                x = self.to[e ^ 1];
+                self.iter[x] = self.next[self.iter[x]];
+            }
+        }
+    }
+
+    fn max_flow(&mut self, limit: u32, start: Instant, tl: u64) -> Option<u32> {
+        let mut flow = 0;
+        while self.bfs() {
+            self.iter.copy_from_slice(&self.head);
+            loop {
+                let f = self.augment();
+                if f == 0 {
+                    break;
+                }
+                flow += f;
+                if flow >= limit {
+                    return Some(flow);
+                }
+            }
+            if start.elapsed().as_secs() > tl {
+                return None;
+            }
+        }
+        Some(flow)
+    }
+
+    fn source_side(&self) -> Vec<bool> {
+        let mut reach = vec![false; self.head.len()];
+        reach[SOURCE] = true;
+        let mut queue = vec![SOURCE];
+        while let Some(x) = queue.pop() {
+            let mut e = self.head[x];
+            while e != usize::MAX {
+                let y = self.to[e];
+                if self.cap[e] > 0 && !reach[y] {
+                    reach[y] = true;
+                    queue.push(y);
+                }
+                e = self.next[e];
+            }
+        }
+        reach
+    }
+}
+
+/// Forward minimum-register retiming. Latches with constant initial values are
+/// moved forward over the combinational logic they exclusively drive, choosing
+/// the register cut by max-flow/min-cut. Moving registers forward keeps the time
+/// axis of the model, so a cex only needs the initial values of the absorbed
+/// latches, which are recorded in the [`Restore`]. The new registers keep the
+/// vars of the cut gates, so an invariant maps back over the original gates.
+pub struct Retime {
+    ts: Transys,
+    rst: Restore,
+    tl: u64,
+}
+
+impl Retime {
+    pub fn new(ts: Transys, cfg: &PreprocConfig, rst: Restore) -> Self {
+        Self {
+            ts,
+            rst,
+            tl: cfg.retime_tl,
+        }
+    }
+
+    /// Retimable region: constant-initialized latches and the gates that only
+    /// depend on them, in topological order.
+    fn region(&self) -> Vec<Var> {
+        let iv = self.rst.init_var();
+        let mut in_region = GHashSet::new();
+        let mut region = Vec::new();
+        for v in self.ts.rel.var_iter() {
+            let is_source = self.ts.is_latch(v)
+                && Some(v) != iv
+                && self.ts.init(v).is_some_and(|i| i.try_constant().is_some())
+                && self.rst.try_restore(v.lit()).is_some();
+            let is_gate = !self.ts.rel.is_leaf(v) && {
+                let dep = self.ts.rel.dep(v);
+                dep.iter().any(|d| in_region.contains(d))
+                    && dep.iter().all(|d| d.is_constant() || in_region.contains(d))
+            };
+            if is_source || is_gate {
+                in_region.insert(v);
+                region.push(v);
+            }
+        }
+        region
+    }
+
+    /// Computes the minimum register cut of the region. Returns the absorbed
+    /// latches and the gates that become registers, or None if no latch can be
+    /// saved.
+    fn min_cut(&self, region: &[Var], start: Instant) -> Option<(Vec<Var>, Vec<Var>)> {
+        let idx: GHashMap<Var, usize> = region.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+        let node_in = |i: usize| 2 + 2 * i;
+        let node_out = |i: usize| 3 + 2 * i;
+        let mut external: GHashSet<Var> = self
+            .ts
+            .bad
+            .iter()
+            .chain(self.ts.constraint.iter())
+            .chain(self.ts.next.values())
+            .chain(self.ts.init.values())
+            .map(|l| l.var())
+            .collect();
+        for v in self.ts.rel.var_iter() {
+            if !self.ts.rel.is_leaf(v) && !idx.contains_key(&v) {
+                external.extend(self.ts.rel.dep(v).iter().copied());
+            }
+        }
+        let mut net = FlowNet::new(2 + 2 * region.len());
+        let mut num_latch = 0;
+        for (i, &v) in region.iter().enumerate() {
+            if self.ts.is_latch(v) {
+                num_latch += 1;
+                net.add_edge(SOURCE, node_in(i), 1);
+                net.add_edge(node_in(i), node_out(i), 1);
+            } else {
+                // gates that can not be mapped back to the original model are
+                // never chosen as registers
+                let cap = if self.rst.try_restore(v.lit()).is_some() {
+                    1
+                } else {
+                    INF
+                };
+                net.add_edge(node_in(i), node_out(i), cap);
+                for d in self.ts.rel.dep(v).iter() {
+                    if let Some(&j) = idx.get(d) {
+                        net.add_edge(node_out(j), node_in(i), INF);
+                        net.add_edge(node_in(i), node_in(j), INF);
+                    }
+                }
+            }
+            if external.contains(&v) {
+                net.add_edge(node_out(i), SINK, INF);
+            }
+        }
+        let flow = net.max_flow(num_latch, start, self.tl)?;
+        debug!("retime: min cut {flow} out of {num_latch} latches");
+        if flow >= num_latch {
+            return None;
+        }
+        let reach = net.source_side();
+        let mut absorbed = Vec::new();
+        let mut register = Vec::new();
+        for (i, &v) in region.iter().enumerate() {
+            if !reach[node_in(i)] {
+                continue;
+            }
+            match (self.ts.is_latch(v), reach[node_out(i)]) {
+                (true, true) => absorbed.push(v),
+                (false, false) => register.push(v),
+                _ => (),
+            }
+        }
+        Some((absorbed, register))
+    }
+
+    /// Retimes once, returns false if no latch is saved.
+    fn retime_once(&mut self, start: Instant) -> bool {
+        if !self.ts.justice.is_empty() {
+            return false;
+        }
+        self.ts.topsort(&mut self.rst);
+        let region = self.region();
+        let Some((absorbed, register)) = self.min_cut(&region, start) else {
+            return false;
+        };
+
+        // initial values of the new registers
+        let init: LitVec = region
+            .iter()
+            .filter_map(|&l| {
+                let i = self.ts.init(l)?.try_constant()?;
+                Some(l.lit().not_if(!i))
+            })
+            .collect();
+        let mut slv = DagCnfSolver::new(&self.ts.rel);
+        if !slv.solve_with_domain(&init, register.iter().copied()) {
+            debug!("retime: initial values of the region conflict with its gates");
+            return false;
+        }
+        let reg_init: Vec<_> = register
+            .iter()
+            .map(|r| Lit::constant(slv.sat_value(r.lit()).unwrap()))
+            .collect();
+
+        // next state functions of the new registers: the logic in front of
+        // the cut is evaluated on the next state functions of the latches
+        let mut map: GHashMap<Var, Lit> = GHashMap::new();
+        map.insert(Var::CONST, Var::CONST.lit());
+        for &v in region.iter() {
+            if self.ts.is_latch(v) {
+                map.insert(v, self.ts.next(v.lit()));
+                continue;
+            }
+            let n = self.ts.copy_rel(v, |l| map[&l.var()].not_if(!l.polarity()));
+            map.insert(v, n.lit());
+        }
+
+        for (&r, &i) in register.iter().zip(reg_init.iter()) {
+            self.ts.rel.del_rel(r);
+            self.ts.add_latch(r, Some(i), map[&r]);
+        }
+        // absorbed latches are removed by coi refinement, record their initial
+        // values for cex restoration before that
+        for &l in absorbed.iter() {
+            let i = self.ts.init(l).unwrap().try_constant().unwrap();
+            self.rst
+                .add_init_state(self.rst.restore(l.lit()).not_if(!i));
+        }
+        let nl = self.ts.latch.len();
+        debug!(
+            "retime: absorbs {} latches into {} registers",
+            absorbed.len(),
+            register.len()
+        );
+        self.ts.simplify(&mut self.rst);
+        self.ts.latch.len() < nl
+    }
+
+    pub fn retime(mut self) -> (Transys, Restore) {
+        let start = Instant::now();
+        let before = self.ts.latch.len();
+        while self.retime_once(start) {
+            if start.elapsed().as_secs() > self.tl {
+                info!("retime: timeout");
+                break;
+            }
+        }
+        info!(
+            "retime: reduces latches from {} to {} in {:.2}s",
+            before,
+            self.ts.latch.len(),
+            start.elapsed().as_secs_f32()
+        );
+        info!("retime: simplified ts: {}", self.ts.statistic());
+        (self.ts, self.rst)
+    }
+}
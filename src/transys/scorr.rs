@@ -1,44 +1,44 @@
 use crate::{
     config::PreprocConfig,
     gipsat::DagCnfSolver,
-    transys::{Transys, TransysIf, certify::Restore},
+    transys::{Transys, TransysIf, certify::Restore, unroll::TransysUnroll},
 };
 use giputils::{bitvec::BitVec, hash::GHashMap};
 use log::{debug, info};
 use logicrs::{Lit, LitVec, Var, VarLMap, satif::Satif};
-use std::time::Instant;
+use rand::{SeedableRng, rngs::StdRng};
+use std::{iter::once, ops::Range, time::Instant};
 
+/// Signal correspondence. [`Scorr::scorr`] merges latches by pairwise
+/// 1-step induction. [`Scorr::kscorr`] obtains candidate equivalence classes
+/// over latches and internal signals by bit-parallel simulation, then refines
+/// them by k-step induction, assuming all candidates in the first k frames,
+/// until a fixpoint is reached.
 pub struct Scorr {
     ts: Transys,
     rst: Restore,
-    init_slv: DagCnfSolver,
-    ind_slv: DagCnfSolver,
     cfg: PreprocConfig,
+    start: Instant,
 }
 
 impl Scorr {
     pub fn new(ts: Transys, cfg: &PreprocConfig, rst: Restore) -> Self {
-        let mut ind_slv = DagCnfSolver::new(&ts.rel);
-        for c in ts.constraint.iter() {
-            ind_slv.add_clause(&[*c]);
-        }
-        let mut init_slv = DagCnfSolver::new(&ts.rel);
-        for c in ts.constraint.iter() {
-            init_slv.add_clause(&[*c]);
-        }
-        ts.load_init(&mut init_slv);
         Self {
             ts,
             rst,
-            ind_slv,
-            init_slv,
             cfg: cfg.clone(),
+            start: Instant::now(),
         }
     }
 
-    fn check_scorr(&mut self, x: Lit, y: Lit) -> bool {
-        if self
-            .init_slv
+    fn check_scorr(
+        &self,
+        init_slv: &mut DagCnfSolver,
+        ind_slv: &mut DagCnfSolver,
+        x: Lit,
+        y: Lit,
+    ) -> bool {
+        if init_slv
             .solve_with_restart_limit(&[], vec![LitVec::from([x, y]), LitVec::from([!x, !y])], 10)
             .is_none_or(|r| r)
         {
@@ -50,7 +50,7 @@ impl Scorr {
         } else {
             self.ts.next(y)
         };
-        self.ind_slv
+        ind_slv
             .solve_with_restart_limit(
                 &[],
                 vec![
@@ -70,6 +70,15 @@ impl Scorr {
         if init.bv_len() == 0 {
             return (self.ts, self.rst);
         }
+        let mut ind_slv = DagCnfSolver::new(&self.ts.rel);
+        for c in self.ts.constraint.iter() {
+            ind_slv.add_clause(&[*c]);
+        }
+        let mut init_slv = DagCnfSolver::new(&self.ts.rel);
+        for c in self.ts.constraint.iter() {
+            init_slv.add_clause(&[*c]);
+        }
+        self.ts.load_init(&mut init_slv);
         let mut rt = self.ts.rt_simulation(&init, 10);
         debug!(
             "scorr: init simulation size: {}, rt simulation size: {}",
@@ -127,7 +136,7 @@ impl Scorr {
                 if y.var() >= x {
                     break;
                 }
-                if self.check_scorr(xl, y) {
+                if self.check_scorr(&mut init_slv, &mut ind_slv, xl, y) {
                     debug!("scorr: {xl} -> {y}");
                     scorr.insert_lit(xl, y);
                     eqc.retain(|l| l.var() != x);
@@ -172,4 +181,222 @@ impl Scorr {
         info!("scorr: simplified ts: {}", self.ts.statistic());
         (self.ts, self.rst)
     }
+
+    #[inline]
+    fn timeout(&self) -> bool {
+        self.start.elapsed().as_secs() > self.cfg.kscorr_tl
+    }
+
+    /// Candidate classes from simulation, the first literal of each class is
+    /// its representative.
+    fn candidate(&self) -> Option<Vec<LitVec>> {
+        let init = self.ts.init_simulation(1);
+        if init.bv_len() == 0 {
+            return None;
+        }
+        let mut state = self.ts.rt_simulation(&init, 10);
+        // the latches sampled from the initial states are sampled in every
+        // state, the others are assigned randomly
+        let mut latch: Vec<_> = self.ts.latch().filter(|v| !init[*v].is_empty()).collect();
+        latch.sort();
+        for i in 0..init.bv_len() {
+            state[Var::CONST].push(false);
+            for &l in latch.iter() {
+                state[l].push(init[l].get(i));
+            }
+        }
+        debug!("kscorr: simulation size: {}", state.bv_len());
+        let mut rng = StdRng::seed_from_u64(0);
+        let sim = self.ts.comb_simulation(&state, &mut rng);
+        let n = state.bv_len();
+        let mask = if n % 64 == 0 {
+            u64::MAX
+        } else {
+            (1 << (n % 64)) - 1
+        };
+        // latches come before gates, so a latch is never replaced by a gate
+        let iv = self.rst.init_var();
+        let mut latch: Vec<_> = self
+            .ts
+            .latch()
+            .filter(|&v| {
+                Some(v) != iv
+                    && self.ts.init(v).is_none_or(|i| i.try_constant().is_some())
+                    && self.rst.try_restore(v.lit()).is_some()
+            })
+            .collect();
+        latch.sort();
+        let gate = self
+            .ts
+            .rel
+            .var_iter()
+            .filter(|&v| !v.is_constant() && !self.ts.rel.is_leaf(v));
+        let mut cand: GHashMap<Vec<u64>, LitVec> = GHashMap::new();
+        for v in once(Var::CONST).chain(latch).chain(gate) {
+            let l = if v.is_constant() {
+                Lit::constant(false)
+            } else {
+                v.lit()
+            };
+            let sig = &sim[v];
+            if sig.first().is_some_and(|w| w & 1 == 1) {
+                let mut neg: Vec<u64> = sig.iter().map(|w| !w).collect();
+                *neg.last_mut().unwrap() &= mask;
+                cand.entry(neg).or_default().push(!l);
+            } else {
+                cand.entry(sig.clone()).or_default().push(l);
+            }
+        }
+        Some(cand.into_values().filter(|c| c.len() > 1).collect())
+    }
+
+    /// Splits the classes by the given valuation, returns whether any class is
+    /// split.
+    fn refine(classes: &mut Vec<LitVec>, val: impl Fn(Lit) -> bool) -> bool {
+        let mut refined = false;
+        let mut res = Vec::new();
+        for c in classes.drain(..) {
+            let rv = val(c[0]);
+            let mut same = LitVec::new();
+            let mut diff = LitVec::new();
+            for &l in c.iter() {
+                if val(l) == rv {
+                    same.push(l);
+                } else {
+                    diff.push(l);
+                }
+            }
+            refined |= !diff.is_empty();
+            res.extend([same, diff].into_iter().filter(|c| c.len() > 1));
+        }
+        *classes = res;
+        refined
+    }
+
+    /// Clause asserting that some candidate differs from its representative in
+    /// one of the frames.
+    fn miter(
+        uts: &mut TransysUnroll<Transys>,
+        slv: &mut impl Satif,
+        classes: &[LitVec],
+        frames: Range<usize>,
+    ) -> LitVec {
+        let mut miter = LitVec::new();
+        for f in frames {
+            for c in classes.iter() {
+                let r = uts.lit_next(c[0], f);
+                for &l in c.iter().skip(1) {
+                    let l = uts.lit_next(l, f);
+                    let d = uts.new_var().lit();
+                    slv.new_var_to(d.var());
+                    slv.add_clause(&[!d, r, l]);
+                    slv.add_clause(&[!d, !r, !l]);
+                    miter.push(d);
+                }
+            }
+        }
+        miter
+    }
+
+    /// Refines the classes until none of them is violated in the first k
+    /// frames from the initial states. Returns None on timeout.
+    fn base(&self, classes: &mut Vec<LitVec>) -> Option<()> {
+        let k = self.cfg.kscorr_k;
+        let mut uts = TransysUnroll::new(&self.ts);
+        uts.unroll_to(k - 1);
+        let mut slv = cadical::CaDiCaL::new();
+        self.ts.load_init(&mut slv);
+        for f in 0..k {
+            uts.load_trans(&mut slv, f, true);
+        }
+        while !classes.is_empty() {
+            if self.timeout() {
+                return None;
+            }
+            let miter = Self::miter(&mut uts, &mut slv, classes, 0..k);
+            if !slv.try_solve(&[], vec![miter])? {
+                break;
+            }
+            let mut refined = false;
+            for f in 0..k {
+                refined |= Self::refine(classes, |l| slv.sat_value(uts.lit_next(l, f)).unwrap());
+            }
+            assert!(refined);
+        }
+        Some(())
+    }
+
+    /// Refines the classes until they are k-inductive. Returns None on
+    /// timeout.
+    fn induction(&self, classes: &mut Vec<LitVec>) -> Option<()> {
+        let k = self.cfg.kscorr_k;
+        let mut uts = TransysUnroll::new(&self.ts);
+        uts.unroll_to(k);
+        let mut slv = cadical::CaDiCaL::new();
+        for f in 0..=k {
+            uts.load_trans(&mut slv, f, true);
+        }
+        while !classes.is_empty() {
+            if self.timeout() {
+                return None;
+            }
+            let mut cst = Vec::new();
+            for f in 0..k {
+                for c in classes.iter() {
+                    let r = uts.lit_next(c[0], f);
+                    for &l in c.iter().skip(1) {
+                        let l = uts.lit_next(l, f);
+                        cst.push(LitVec::from([r, !l]));
+                        cst.push(LitVec::from([!r, l]));
+                    }
+                }
+            }
+            cst.push(Self::miter(&mut uts, &mut slv, classes, k..k + 1));
+            if !slv.try_solve(&[], cst)? {
+                break;
+            }
+            let refined = Self::refine(classes, |l| slv.sat_value(uts.lit_next(l, k)).unwrap());
+            assert!(refined);
+        }
+        Some(())
+    }
+
+    pub fn kscorr(mut self) -> (Transys, Restore) {
+        self.ts.topsort(&mut self.rst);
+        let Some(mut classes) = self.candidate() else {
+            return (self.ts, self.rst);
+        };
+        let num_cand: usize = classes.iter().map(|c| c.len() - 1).sum();
+        debug!(
+            "kscorr: {num_cand} candidates in {} classes after simulation",
+            classes.len()
+        );
+        if self.base(&mut classes).is_none() || self.induction(&mut classes).is_none() {
+            info!("kscorr: timeout");
+            return (self.ts, self.rst);
+        }
+        let mut eqs = VarLMap::new();
+        for c in classes.iter() {
+            for &l in c.iter().skip(1) {
+                debug!("kscorr: {l} -> {}", c[0]);
+                eqs.insert_lit(l, c[0]);
+            }
+        }
+        let Self {
+            mut ts,
+            mut rst,
+            cfg,
+            start,
+        } = self;
+        info!(
+            "kscorr: proves {} equivalences out of {num_cand} candidates in {:.2}s",
+            eqs.len(),
+            start.elapsed().as_secs_f32()
+        );
+        ts.replace(&eqs, &mut rst);
+        rst.set_eq_depth(cfg.kscorr_k);
+        ts.simplify(&mut rst);
+        info!("kscorr: simplified ts: {}", ts.statistic());
+        (ts, rst)
+    }
 }
@@ -1,7 +1,10 @@
 use super::{Transys, TransysIf};
 use crate::{
     config::PreprocConfig,
-    transys::{certify::Restore, frts::FrTs, scorr::Scorr},
+    transys::{
+        certify::Restore, enlarge::Enlarge, frts::FrTs, reparam::Reparam, retime::Retime,
+        scorr::Scorr, tsim::TSim,
+    },
 };
 use giputils::hash::GHashSet;
 use log::{debug, info};
@@ -142,10 +145,30 @@ impl Transys {
                 let scorr = Scorr::new(ts, cfg, rst);
                 (ts, rst) = scorr.scorr();
             }
+            if cfg.kscorr {
+                let kscorr = Scorr::new(ts, cfg, rst);
+                (ts, rst) = kscorr.kscorr();
+            }
             if cfg.frts {
                 let frts = FrTs::new(ts, cfg, rst);
                 (ts, rst) = frts.fr();
             }
+            if cfg.tsim {
+                let tsim = TSim::new(ts, cfg, rst);
+                (ts, rst) = tsim.tsim();
+            }
+            if cfg.retime {
+                let retime = Retime::new(ts, cfg, rst);
+                (ts, rst) = retime.retime();
+            }
+            if cfg.reparam {
+                let reparam = Reparam::new(ts, cfg, rst);
+                (ts, rst) = reparam.reparam();
+            }
+            if cfg.enlarge {
+                let enlarge = Enlarge::new(ts, cfg, rst);
+                (ts, rst) = enlarge.enlarge();
+            }
         }
         info!("preprocessed ts has {}", ts.statistic());
         (ts, rst)
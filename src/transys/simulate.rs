@@ -2,9 +2,9 @@ use crate::{
     gipsat::DagCnfSolver,
     transys::{Transys, TransysIf},
 };
-use giputils::bitvec::BitVec;
-use logicrs::{LitVec, Var, VarBitVec, satif::Satif};
-use rand::{SeedableRng, rngs::StdRng};
+use giputils::{bitvec::BitVec, hash::GHashSet};
+use logicrs::{LitVec, Var, VarBitVec, VarMap, satif::Satif};
+use rand::{RngExt, SeedableRng, rngs::StdRng};
 
 struct Simulate<'t> {
     ts: &'t Transys,
@@ -54,16 +54,23 @@ impl Transys {
         self.load_init(&mut slv);
         let mut sim = VarBitVec::new();
         sim.reserve(self.max_var());
+        // a latch left free in some sample is not sampled at all, so all
+        // sampled latches have a value in every sample
+        let mut free = GHashSet::new();
         while sim.bv_len() < num_word * BitVec::WORD_SIZE {
             if !slv.solve(&[]) {
                 break;
             }
             let mut block = LitVec::new();
             for &v in self.latch.iter() {
+                if free.contains(&v) {
+                    continue;
+                }
                 if let Some(a) = slv.sat_value(v.lit()) {
                     block.push(!slv.sat_value_lit(v).unwrap());
                     sim[v].push(a);
                 } else {
+                    free.insert(v);
                     sim[v].clear();
                 }
             }
@@ -193,4 +200,55 @@ impl Transys {
         }
         simulate.sim
     }
+
+    /// Bit-parallel simulation of all signals on the sampled latch values of
+    /// `state`, 64 samples per word. Inputs and latches without samples are
+    /// assigned randomly. Requires the rel to be topologically sorted.
+    pub fn comb_simulation(&self, state: &VarBitVec, rng: &mut StdRng) -> VarMap<Vec<u64>> {
+        let n = state.bv_len();
+        let nw = n.div_ceil(64);
+        let mask = if n % 64 == 0 {
+            u64::MAX
+        } else {
+            (1 << (n % 64)) - 1
+        };
+        let mut sim: VarMap<Vec<u64>> = VarMap::new_with(self.max_var());
+        for v in self.rel.var_iter() {
+            let mut res = vec![0; nw];
+            if v.is_constant() {
+                // constant false
+            } else if self.rel.is_leaf(v) {
+                if self.is_latch(v) && !state[v].is_empty() {
+                    for i in 0..n {
+                        if state[v].get(i) {
+                            res[i / 64] |= 1 << (i % 64);
+                        }
+                    }
+                } else {
+                    res.iter_mut().for_each(|w| *w = rng.random());
+                }
+            } else {
+                // v holds iff the body of a clause with positive v is false
+                for cls in self.rel[v].iter() {
+                    if !cls.iter().any(|l| l.var() == v && l.polarity()) {
+                        continue;
+                    }
+                    let mut body = vec![u64::MAX; nw];
+                    for l in cls.iter().filter(|l| l.var() != v) {
+                        for (b, &w) in body.iter_mut().zip(sim[l.var()].iter()) {
+                            *b &= if l.polarity() { !w } else { w };
+                        }
+                    }
+                    for (r, b) in res.iter_mut().zip(body) {
+                        *r |= b;
+                    }
+                }
+            }
+            if let Some(w) = res.last_mut() {
+                *w &= mask;
+            }
+            sim[v] = res;
+        }
+        sim
+    }
 }
@@ -0,0 +1,359 @@
+#[cfg(test)]
+use super::TransysIf;
+#[cfg(test)]
+use logicrs::{DagCnf, Var};
+
+#[cfg(test)]
+impl super::Transys {
+    /// An empty ts with `I` inputs and `N` further vars, for the models of
+    /// the tests.
+    pub(crate) fn test_model<const I: usize, const N: usize>() -> (Self, [Var; I], [Var; N]) {
+        let mut ts = Self {
+            rel: DagCnf::new(),
+            ..Default::default()
+        };
+        let input = [(); I].map(|_| ts.new_var());
+        for i in input {
+            ts.add_input(i);
+        }
+        let vars = [(); N].map(|_| ts.new_var());
+        (ts, input, vars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use crate::{
+        BlEngine, Engine,
+        bmc::{BMC, BMCConfig},
+        config::{EngineConfig, PreprocConfig},
+        create_bl_engine,
+        frontend::{Frontend, aig::AigFrontend, certificate_check},
+        ic3::{IC3, IC3Config},
+        kind::{Kind, KindConfig},
+        transys::{
+            certify::Restore, enlarge::Enlarge, reparam::Reparam, retime::Retime, scorr::Scorr,
+            tsim::TSim,
+        },
+    };
+    use ::aig::Aig;
+    use clap::Parser;
+    use logicrs::VarSymbols;
+    use std::fs;
+
+    /// Preprocessing with only the passes enabled by `pass`.
+    fn preproc_only(cfg: &mut PreprocConfig, pass: impl FnOnce(&mut PreprocConfig)) {
+        cfg.bve = false;
+        cfg.scorr = false;
+        cfg.frts = false;
+        pass(cfg);
+    }
+
+    /// Checks that bmc on `ts` preprocessed by `pass` finds a cex, which is
+    /// restored to a cex of `ts` of `len` steps.
+    fn check_cex(ts: &Transys, pass: impl FnOnce(&mut PreprocConfig), len: usize) {
+        let mut cfg = BMCConfig::default();
+        preproc_only(&mut cfg.preproc, pass);
+        let mut bmc = BMC::new(cfg, ts.clone());
+        assert!(bmc.check().is_sat());
+        let cex = bmc.cex();
+        assert_eq!(cex.len(), len);
+        assert!(cex.replay(ts, 0).is_some());
+    }
+
+    /// Checks that ic3, and k-induction if `kind`, prove `ts` preprocessed
+    /// by `pass`, with proofs that are restored to inductive proofs of `ts`.
+    fn check_proof(ts: &Transys, pass: impl Fn(&mut PreprocConfig), kind: bool) {
+        let mut cfg = IC3Config::default();
+        preproc_only(&mut cfg.preproc, &pass);
+        let mut ic3 = IC3::new(cfg, ts.clone(), VarSymbols::default());
+        assert!(ic3.check().is_unsat());
+        assert!(ic3.proof().is_inductive());
+        if kind {
+            let mut cfg = KindConfig::default();
+            preproc_only(&mut cfg.preproc, &pass);
+            let mut kind = Kind::new(cfg, ts.clone());
+            assert!(kind.check().is_unsat());
+            assert!(kind.proof().is_inductive());
+        }
+    }
+
+    /// Checks with certifaiger that the certificates of `engines` on `ts`
+    /// preprocessed by `pass` hold on the aiger of `ts`.
+    fn check_certified(ts: &Transys, pass: impl Fn(&mut PreprocConfig), engines: &[&str]) {
+        let aig = Aig::from(ts);
+        let model = tempfile::Builder::new().suffix(".aag").tempfile().unwrap();
+        fs::write(model.path(), format!("{aig}")).unwrap();
+        let mut frontend = AigFrontend::new(aig);
+        for &name in engines {
+            let mut cfg = EngineConfig::parse_from(["", name]);
+            let preproc = match &mut cfg {
+                EngineConfig::IC3(c) => &mut c.preproc,
+                EngineConfig::Kind(c) => &mut c.preproc,
+                EngineConfig::BMC(c) => &mut c.preproc,
+                _ => unreachable!(),
+            };
+            preproc_only(preproc, &pass);
+            let (ts, symbols) = frontend.ts();
+            let mut engine = create_bl_engine(cfg, ts, symbols);
+            let res = engine.check();
+            let cert = frontend.bl_certificate(engine.certificate(res));
+            let file = tempfile::NamedTempFile::new().unwrap();
+            fs::write(file.path(), format!("{cert}")).unwrap();
+            assert!(
+                certificate_check(&model.path().to_path_buf(), file.path()),
+                "certificate of {name}"
+            );
+        }
+    }
+
+    /// Two input latches whose conjunction is latched into the bad latch, the
+    /// bad is reached at depth 2.
+    fn and_pipeline() -> Transys {
+        let (mut ts, [i0, i1], [a, b, c]) = Transys::test_model();
+        ts.add_latch(a, Some(Lit::constant(false)), i0.lit());
+        ts.add_latch(b, Some(Lit::constant(false)), i1.lit());
+        let g = ts.rel.new_and([a.lit(), b.lit()]);
+        ts.add_latch(c, Some(Lit::constant(false)), g);
+        ts.bad = LitVec::from(c.lit());
+        ts
+    }
+
+    /// Latches `a` and `b` loading inputs in alternating cycles of the toggle
+    /// `q`, their conjunction is latched into the bad latch `c`. Retiming
+    /// absorbs `a` and `b` into a register of `a & b`, which is never set.
+    fn alternating_loads() -> Transys {
+        let (mut ts, [i0, i1], [q, a, b, c]) = Transys::test_model();
+        ts.add_latch(q, Some(Lit::constant(false)), !q.lit());
+        let an = ts.rel.new_and([i0.lit(), !q.lit()]);
+        let bn = ts.rel.new_and([i1.lit(), q.lit()]);
+        ts.add_latch(a, Some(Lit::constant(false)), an);
+        ts.add_latch(b, Some(Lit::constant(false)), bn);
+        let g = ts.rel.new_and([a.lit(), b.lit()]);
+        ts.add_latch(c, Some(Lit::constant(false)), g);
+        ts.bad = LitVec::from(c.lit());
+        ts
+    }
+
+    /// The latch count of `ts` retimed.
+    fn retimed_latches(ts: &Transys) -> usize {
+        let (rts, rst) =
+            Retime::new(ts.clone(), &PreprocConfig::default(), Restore::new(ts)).retime();
+        assert!(rst.is_retimed());
+        rts.latch.len()
+    }
+
+    #[test]
+    fn retime_cex() {
+        let ts = and_pipeline();
+        // `a` and `b` become one register of `a & b`
+        assert_eq!(retimed_latches(&ts), 2);
+        check_cex(&ts, |p| p.retime = true, 3);
+    }
+
+    #[test]
+    fn retime_proof() {
+        let ts = alternating_loads();
+        assert_eq!(retimed_latches(&ts), 3);
+        check_proof(&ts, |p| p.retime = true, true);
+    }
+
+    #[test]
+    #[ignore = "needs the certifaiger docker image"]
+    fn retime_certified() {
+        check_certified(&and_pipeline(), |p| p.retime = true, &["bmc"]);
+        check_certified(&alternating_loads(), |p| p.retime = true, &["ic3", "kind"]);
+    }
+
+    /// Two equal shift registers of two latches, returns the last latches.
+    fn dup_shift() -> (Transys, Var, Var) {
+        let (mut ts, [i], [x1, y1, x2, y2]) = Transys::test_model();
+        ts.add_latch(x1, Some(Lit::constant(false)), i.lit());
+        ts.add_latch(y1, Some(Lit::constant(false)), i.lit());
+        ts.add_latch(x2, Some(Lit::constant(false)), x1.lit());
+        ts.add_latch(y2, Some(Lit::constant(false)), y1.lit());
+        (ts, x2, y2)
+    }
+
+    /// A one-hot ring of four latches, the bad is two opposite latches being
+    /// set together. The bad is false by 2-step induction, but not by 1-step
+    /// induction.
+    fn one_hot_ring() -> Transys {
+        let (mut ts, [], p) = Transys::test_model::<0, 4>();
+        for (i, &l) in p.iter().enumerate() {
+            ts.add_latch(l, Some(Lit::constant(i == 0)), p[(i + 3) % 4].lit());
+        }
+        ts.bad = LitVec::from(ts.rel.new_and([p[0].lit(), p[2].lit()]));
+        ts
+    }
+
+    /// 2-step signal correspondence.
+    fn kscorr(p: &mut PreprocConfig) {
+        p.kscorr = true;
+        p.kscorr_k = 2;
+    }
+
+    /// `ts` after 2-step signal correspondence.
+    fn kscorred(ts: &Transys) -> (Transys, Restore) {
+        let cfg = PreprocConfig {
+            kscorr_k: 2,
+            ..Default::default()
+        };
+        Scorr::new(ts.clone(), &cfg, Restore::new(ts)).kscorr()
+    }
+
+    #[test]
+    fn kscorr_cex() {
+        let (mut ts, x2, y2) = dup_shift();
+        ts.bad = LitVec::from(ts.rel.new_and([x2.lit(), y2.lit()]));
+        let (kts, rst) = kscorred(&ts);
+        // the latches of one shift register are merged into the other one
+        assert_eq!(kts.latch.len(), 2);
+        assert!(!rst.eq_invariant().is_empty());
+        check_cex(&ts, kscorr, 3);
+    }
+
+    #[test]
+    fn kscorr_proof() {
+        let ts = one_hot_ring();
+        let (kts, rst) = kscorred(&ts);
+        // the bad is merged into the constant by 2-step induction
+        assert_eq!(kts.bad[0], Lit::constant(false));
+        assert_eq!(rst.eq_depth(), 2);
+        check_proof(&ts, kscorr, true);
+    }
+
+    #[test]
+    #[ignore = "needs the certifaiger docker image"]
+    fn kscorr_certified() {
+        let (mut ts, x2, y2) = dup_shift();
+        ts.bad = LitVec::from(ts.rel.new_and([x2.lit(), y2.lit()]));
+        check_certified(&ts, kscorr, &["bmc"]);
+        check_certified(&one_hot_ring(), kscorr, &["ic3", "kind"]);
+    }
+
+    /// An input latched into `c`, the bad is `c` once the latches `a` and `b`
+    /// have left their initial values. `a` and `b` are constant after a
+    /// transient step.
+    fn late_bad() -> Transys {
+        let (mut ts, [i], [a, b, c]) = Transys::test_model();
+        ts.add_latch(a, Some(Lit::constant(false)), Lit::constant(true));
+        ts.add_latch(b, Some(Lit::constant(false)), Lit::constant(true));
+        ts.add_latch(c, Some(Lit::constant(false)), i.lit());
+        ts.bad = LitVec::from(ts.rel.new_and([a.lit(), b.lit(), c.lit()]));
+        ts
+    }
+
+    /// Adds a ring of three latches passing a single token, returns the bad
+    /// of two tokens, which is unreachable but not 1-inductive.
+    fn add_ring(ts: &mut Transys) -> Lit {
+        let [r0, r1, r2] = [(); 3].map(|_| ts.new_var());
+        ts.add_latch(r0, Some(Lit::constant(true)), r2.lit());
+        ts.add_latch(r1, Some(Lit::constant(false)), r0.lit());
+        ts.add_latch(r2, Some(Lit::constant(false)), r1.lit());
+        ts.rel.new_and([r0.lit(), r1.lit()])
+    }
+
+    /// The latch `z` stuck at its initial value, the latches `a` and `b` of
+    /// [`late_bad`] and a ring of [`add_ring`], the bad is `z`, `!a & b` or
+    /// the bad of the ring.
+    fn stuck_and_transient() -> Transys {
+        let (mut ts, [i], [z, a, b]) = Transys::test_model();
+        let zn = ts.rel.new_and([z.lit(), i.lit()]);
+        ts.add_latch(z, Some(Lit::constant(false)), zn);
+        ts.add_latch(a, Some(Lit::constant(false)), Lit::constant(true));
+        ts.add_latch(b, Some(Lit::constant(false)), Lit::constant(true));
+        let ab = ts.rel.new_and([!a.lit(), b.lit()]);
+        let ring = add_ring(&mut ts);
+        ts.bad = LitVec::from(ts.rel.new_or([zn, ab, ring]));
+        ts
+    }
+
+    /// Checks that ternary simulation removes latches of `ts`.
+    fn check_tsim_shrinks(ts: &Transys) {
+        let (rts, _) = TSim::new(ts.clone(), &PreprocConfig::default(), Restore::new(ts)).tsim();
+        assert!(rts.latch.len() < ts.latch.len());
+    }
+
+    #[test]
+    fn tsim_cex() {
+        let ts = late_bad();
+        check_tsim_shrinks(&ts);
+        check_cex(&ts, |p| p.tsim = true, 2);
+    }
+
+    #[test]
+    fn tsim_proof() {
+        let ts = stuck_and_transient();
+        check_tsim_shrinks(&ts);
+        check_proof(&ts, |p| p.tsim = true, true);
+    }
+
+    #[test]
+    #[ignore = "needs the certifaiger docker image"]
+    fn tsim_certified() {
+        check_certified(&late_bad(), |p| p.tsim = true, &["bmc"]);
+        check_certified(&stuck_and_transient(), |p| p.tsim = true, &["ic3", "kind"]);
+    }
+
+    /// Latches `c` and `d` of the conjunction of three inputs and of their
+    /// negations, the pair never takes all four values.
+    fn input_cuts() -> (Transys, Var, Var) {
+        let (mut ts, [i0, i1, i2], [c, d]) = Transys::test_model();
+        let g1 = ts.rel.new_and([i0.lit(), i1.lit(), i2.lit()]);
+        let g2 = ts.rel.new_and([!i0.lit(), !i1.lit(), !i2.lit()]);
+        ts.add_latch(c, Some(Lit::constant(false)), g1);
+        ts.add_latch(d, Some(Lit::constant(false)), g2);
+        (ts, c, d)
+    }
+
+    #[test]
+    fn reparam_cex() {
+        let (mut ts, c, d) = input_cuts();
+        ts.bad = LitVec::from(ts.rel.new_or([c.lit(), d.lit()]));
+        check_cex(&ts, |p| p.reparam = true, 2);
+    }
+
+    #[test]
+    fn reparam_proof() {
+        let (mut ts, c, d) = input_cuts();
+        ts.bad = LitVec::from(ts.rel.new_and([c.lit(), d.lit()]));
+        let (rts, _) =
+            Reparam::new(ts.clone(), &PreprocConfig::default(), Restore::new(&ts)).reparam();
+        // the three inputs only reach `c` and `d`
+        assert_eq!(rts.input.len(), 2);
+        check_proof(&ts, |p| p.reparam = true, false);
+    }
+
+    /// A three bit counter counting the cycles with an enabling input, the
+    /// bad is the value 5, first reached at depth 5.
+    fn counter() -> Transys {
+        let (mut ts, [en], bits) = Transys::test_model::<1, 3>();
+        let mut carry = en.lit();
+        for b in bits {
+            let n = !ts.rel.new_xnor(b.lit(), carry);
+            ts.add_latch(b, Some(Lit::constant(false)), n);
+            carry = ts.rel.new_and([b.lit(), carry]);
+        }
+        let [b0, b1, b2] = bits.map(|b| b.lit());
+        ts.bad = LitVec::from(ts.rel.new_and([b0, !b1, b2]));
+        ts
+    }
+
+    #[test]
+    fn enlarge_cex() {
+        let ts = counter();
+        let cfg = PreprocConfig::default();
+        let (_, rst) = Enlarge::new(ts.clone(), &cfg, Restore::new(&ts)).enlarge();
+        assert_eq!(rst.enlarge(), cfg.enlarge_k);
+        check_cex(&ts, |p| p.enlarge = true, 6);
+    }
+
+    #[test]
+    fn enlarge_proof() {
+        let (mut ts, [], []) = Transys::test_model::<0, 0>();
+        ts.bad = LitVec::from(add_ring(&mut ts));
+        check_proof(&ts, |p| p.enlarge = true, false);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    config::PreprocConfig,
+    transys::{
+        Transys, TransysIf,
+        certify::{Restore, TimeRestore, time_mux},
+        unroll::TransysUnroll,
+    },
+};
+use giputils::hash::GHashMap;
+use log::{debug, info};
+use logicrs::{Lit, LitVec, Var, VarLMap, VarMap, satif::Satif};
+use std::time::Instant;
+
+const MAX_STEP: usize = 1000;
+
+/// Ternary simulation based constant latch detection. The model is simulated
+/// from the initial states with unknown inputs until a state repeats, latches
+/// that are constant in the whole cycle are constant after some step. A latch
+/// that only becomes constant after a transient prefix, with known values in
+/// the prefix, is replaced by a function of time latches counting the prefix
+/// steps. Only replacements that are inductive together are applied.
+pub struct TSim {
+    ts: Transys,
+    rst: Restore,
+    max_prefix: usize,
+}
+
+impl TSim {
+    pub fn new(mut ts: Transys, cfg: &PreprocConfig, mut rst: Restore) -> Self {
+        ts.topsort(&mut rst);
+        Self {
+            ts,
+            rst,
+            max_prefix: cfg.tsim_prefix,
+        }
+    }
+
+    #[inline]
+    fn lit_val(val: &VarMap<Option<bool>>, l: Lit) -> Option<bool> {
+        val[l.var()].map(|v| v == l.polarity())
+    }
+
+    /// Ternary evaluation of all signals on the given latch values.
+    fn eval(&self, state: &[Option<bool>]) -> VarMap<Option<bool>> {
+        let mut val: VarMap<Option<bool>> = VarMap::new_with(self.ts.max_var());
+        val[Var::CONST] = Some(false);
+        for (&l, &s) in self.ts.latch.iter().zip(state.iter()) {
+            val[l] = s;
+        }
+        for v in self.ts.rel.var_iter() {
+            if v.is_constant() || self.ts.rel.is_leaf(v) {
+                continue;
+            }
+            for cls in self.ts.rel[v].iter() {
+                if cls
+                    .iter()
+                    .filter(|l| l.var() != v)
+                    .all(|&l| Self::lit_val(&val, l) == Some(false))
+                {
+                    val[v] = cls.iter().find(|l| l.var() == v).map(|l| l.polarity());
+                    break;
+                }
+            }
+        }
+        val
+    }
+
+    /// Simulates until a state repeats. Returns the latches that are constant
+    /// from some step on, with their values up to that step, or None if no
+    /// state repeats.
+    fn simulate(&self) -> Option<Vec<(Var, Vec<bool>)>> {
+        let mut state: Vec<_> = self
+            .ts
+            .latch
+            .iter()
+            .map(|l| self.ts.init(*l).and_then(|i| i.try_constant()))
+            .collect();
+        let mut trace = vec![state.clone()];
+        let mut seen = GHashMap::from_iter([(state.clone(), 0)]);
+        let cycle = loop {
+            if trace.len() > MAX_STEP {
+                return None;
+            }
+            let val = self.eval(&state);
+            state = self
+                .ts
+                .latch
+                .iter()
+                .map(|l| Self::lit_val(&val, self.ts.next(l.lit())))
+                .collect();
+            if let Some(&i) = seen.get(&state) {
+                break i;
+            }
+            seen.insert(state.clone(), trace.len());
+            trace.push(state.clone());
+        };
+        debug!(
+            "tsim: cycle from step {cycle} of length {}",
+            trace.len() - cycle
+        );
+        let mut res = Vec::new();
+        for (j, &l) in self.ts.latch.iter().enumerate() {
+            let Some(c) = trace[cycle][j] else {
+                continue;
+            };
+            if trace[cycle..].iter().any(|s| s[j] != Some(c)) {
+                continue;
+            }
+            let mut from = cycle;
+            while from > 0 && trace[from - 1][j] == Some(c) {
+                from -= 1;
+            }
+            // unknown values in the prefix can not be expressed over time
+            let values: Option<Vec<bool>> = trace[..=from].iter().map(|s| s[j]).collect();
+            if let Some(values) = values {
+                res.push((l, values));
+            }
+        }
+        Some(res)
+    }
+
+    /// Adds `n` time latches, the `i`-th latch is set from step `i + 1` on.
+    fn add_time(ts: &mut Transys, n: usize) -> Vec<Var> {
+        let mut time: Vec<Var> = Vec::new();
+        for _ in 0..n {
+            let t = ts.new_var();
+            let next = time.last().map_or(Lit::constant(true), |p| p.lit());
+            ts.add_latch(t, Some(Lit::constant(false)), next);
+            time.push(t);
+        }
+        time
+    }
+
+    /// Drops constant latches until the remaining ones are inductive, assuming
+    /// all of them in the current state. They hold in the initial states by
+    /// the simulation. Returns None if a SAT call is interrupted.
+    fn houdini(&self, mut consts: Vec<(Var, Vec<bool>)>) -> Option<Vec<(Var, Vec<bool>)>> {
+        let mut ts = self.ts.clone();
+        let prefix = consts.iter().map(|(_, v)| v.len() - 1).max().unwrap_or(0);
+        let time = Self::add_time(&mut ts, prefix);
+        let eqs: Vec<Lit> = consts
+            .iter()
+            .map(|(l, values)| {
+                let m = time_mux(&mut ts.rel, &time, values);
+                ts.rel.new_xnor(l.lit(), m)
+            })
+            .collect();
+        let mut uts = TransysUnroll::new(&ts);
+        uts.unroll_to(1);
+        let mut slv = cadical::CaDiCaL::new();
+        for f in 0..=1 {
+            uts.load_trans(&mut slv, f, true);
+        }
+        let mut keep = vec![true; eqs.len()];
+        loop {
+            let kept = || eqs.iter().zip(keep.iter()).filter(|(_, k)| **k);
+            let assump: LitVec = kept().map(|(&e, _)| uts.lit_next(e, 0)).collect();
+            let miter: LitVec = kept().map(|(&e, _)| !uts.lit_next(e, 1)).collect();
+            if miter.is_empty() || !slv.try_solve(&assump, vec![miter])? {
+                break;
+            }
+            for (&e, k) in eqs.iter().zip(keep.iter_mut()) {
+                if *k && !slv.sat_value(uts.lit_next(e, 1)).unwrap() {
+                    *k = false;
+                }
+            }
+        }
+        let mut keep = keep.into_iter();
+        consts.retain(|_| keep.next().unwrap());
+        Some(consts)
+    }
+
+    pub fn tsim(mut self) -> (Transys, Restore) {
+        let start = Instant::now();
+        if !self.ts.justice.is_empty() {
+            return (self.ts, self.rst);
+        }
+        let Some(consts) = self.simulate() else {
+            info!("tsim: no fixpoint within {MAX_STEP} steps");
+            return (self.ts, self.rst);
+        };
+        let iv = self.rst.init_var();
+        let consts: Vec<_> = consts
+            .into_iter()
+            .filter(|(l, values)| {
+                Some(*l) != iv
+                    && values.len() <= self.max_prefix + 1
+                    && self.rst.try_restore(l.lit()).is_some()
+            })
+            .collect();
+        let Some(mut consts) = self.houdini(consts) else {
+            info!("tsim: interrupted");
+            return (self.ts, self.rst);
+        };
+        let mut transient = consts.iter().filter(|(_, v)| v.len() > 1).count();
+        let mut prefix = consts.iter().map(|(_, v)| v.len() - 1).max().unwrap_or(0);
+        if transient > 0 && transient <= prefix {
+            debug!("tsim: {transient} transient latches do not pay off {prefix} time latches");
+            consts.retain(|(_, v)| v.len() == 1);
+            let Some(c) = self.houdini(consts) else {
+                info!("tsim: interrupted");
+                return (self.ts, self.rst);
+            };
+            consts = c;
+            transient = 0;
+            prefix = 0;
+        }
+        if consts.is_empty() {
+            info!("tsim: no constant latch found");
+            return (self.ts, self.rst);
+        }
+        let time = Self::add_time(&mut self.ts, prefix);
+        let mut trst = TimeRestore::default();
+        for &t in time.iter() {
+            let v = self.rst.new_virtual_var();
+            self.rst.add_restore(t, v);
+            trst.time.push(v);
+        }
+        let mut eqs = VarLMap::new();
+        for (l, values) in consts.iter() {
+            let r = time_mux(&mut self.ts.rel, &time, values);
+            if self.rst.try_restore(r).is_none() {
+                let v = self.rst.new_virtual_var();
+                self.rst.add_restore(r.var(), v);
+                let values = values.iter().map(|&b| b == r.polarity()).collect();
+                trst.transient.push((v, values));
+            }
+            debug!("tsim: {l} -> {values:?}");
+            self.ts.init.remove(l);
+            eqs.insert_lit(l.lit(), r);
+        }
+        if !trst.time.is_empty() {
+            self.rst.add_time(trst);
+        }
+        self.ts.replace(&eqs, &mut self.rst);
+        self.ts.topsort(&mut self.rst);
+        self.ts.simplify(&mut self.rst);
+        info!(
+            "tsim: replaces {} latches, {transient} of them after up to {prefix} steps, in {:.2}s",
+            consts.len(),
+            start.elapsed().as_secs_f32()
+        );
+        info!("tsim: simplified ts: {}", self.ts.statistic());
+        (self.ts, self.rst)
+    }
+}
@@ -391,7 +391,7 @@ fn memory_status_groups(stats: MemoryStats) -> (Vec<Span<'static>>, Vec<Span<'st
     )
 }
 
-fn format_kib(kib: u64) -> String {
+pub(crate) fn format_kib(kib: u64) -> String {
     const KIB_PER_MIB: u64 = 1024;
     const KIB_PER_GIB: u64 = 1024 * 1024;
 
@@ -1,11 +1,16 @@
-use crate::{McBlCertificate, McResult};
+use crate::McResult;
 use giputils::TerminateCtrl;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use logicrs::LitVec;
+use nix::{
+    sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal, sigaction},
+    unistd,
+};
 use std::{
+    ffi::c_int,
     process::exit,
     sync::{
-        Arc,
+        Arc, Mutex, Once,
         atomic::{AtomicBool, Ordering},
     },
 };
@@ -41,6 +46,7 @@ impl TerminateCtrl for EngineCtrl {
 }
 
 pub struct InterruptHandle {
+    ctrl: Arc<dyn TerminateCtrl>,
     interrupted: Arc<AtomicBool>,
 }
 
@@ -48,19 +54,93 @@ impl InterruptHandle {
     pub fn is_interrupted(&self) -> bool {
         self.interrupted.load(Ordering::SeqCst)
     }
+
+    /// Terminates the engine as Ctrl-C does, without counting as an interrupt.
+    pub fn terminate(&self) {
+        self.ctrl.terminate();
+    }
 }
 
+type InterruptTarget = (Arc<dyn TerminateCtrl>, Arc<AtomicBool>);
+
+static INTERRUPT_TARGET: Mutex<Option<InterruptTarget>> = Mutex::new(None);
+static INTERRUPT_HANDLER: Once = Once::new();
+static INTERRUPT_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// Terminates `ctrl` on Ctrl-C, a second Ctrl-C exits at once. The handler is
+/// registered once per process and follows the latest installed engine.
 pub fn install_interrupt_handler(ctrl: Arc<dyn TerminateCtrl>) -> InterruptHandle {
     let interrupted = Arc::new(AtomicBool::new(false));
-    let handler_interrupted = interrupted.clone();
-    let _ = ctrlc::set_handler(move || {
-        if handler_interrupted.swap(true, Ordering::SeqCst) {
-            crate::ui::restore_terminal();
-            exit(130);
-        }
-        ctrl.terminate();
+    *INTERRUPT_TARGET.lock().unwrap() = Some((ctrl.clone(), interrupted.clone()));
+    INTERRUPT_HANDLER.call_once(|| {
+        let _ = ctrlc::set_handler(|| {
+            INTERRUPT_RECEIVED.store(true, Ordering::SeqCst);
+            let Some((ctrl, interrupted)) = INTERRUPT_TARGET.lock().unwrap().clone() else {
+                return;
+            };
+            if interrupted.swap(true, Ordering::SeqCst) {
+                crate::ui::restore_terminal();
+                exit(130);
+            }
+            ctrl.terminate();
+        });
     });
-    InterruptHandle { interrupted }
+    InterruptHandle { ctrl, interrupted }
+}
+
+/// Whether Ctrl-C was pressed since [`install_interrupt_handler`] was first
+/// called, for callers that run several engines in a row.
+pub fn interrupt_received() -> bool {
+    INTERRUPT_RECEIVED.load(Ordering::SeqCst)
+}
+
+static FORK_INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_fork_interrupt(_: c_int) {
+    if FORK_INTERRUPTED.swap(true, Ordering::SeqCst) {
+        unistd::_exit(130);
+    }
+}
+
+/// Fork-safe Ctrl-C handling. Unlike [`install_interrupt_handler`], no thread
+/// is spawned, so processes can still be forked afterwards: the signal handler
+/// only sets a flag, which the owner polls. A second Ctrl-C exits at once.
+/// The previous handler is restored on drop, and learns of the interrupt.
+pub struct ForkInterrupt {
+    /// the Ctrl-C handler before, restored on drop
+    old: SigAction,
+}
+
+impl ForkInterrupt {
+    pub fn install() -> Self {
+        FORK_INTERRUPTED.store(false, Ordering::SeqCst);
+        let action = SigAction::new(
+            SigHandler::Handler(on_fork_interrupt),
+            SaFlags::SA_RESTART,
+            SigSet::empty(),
+        );
+        let old = unsafe { sigaction(Signal::SIGINT, &action) }.unwrap();
+        Self { old }
+    }
+
+    /// Ignores Ctrl-C in a forked child, which is stopped by its parent.
+    pub fn ignore_in_child() {
+        let action = SigAction::new(SigHandler::SigIgn, SaFlags::empty(), SigSet::empty());
+        unsafe { sigaction(Signal::SIGINT, &action) }.unwrap();
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        FORK_INTERRUPTED.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for ForkInterrupt {
+    fn drop(&mut self) {
+        let _ = unsafe { sigaction(Signal::SIGINT, &self.old) };
+        if self.is_interrupted() {
+            INTERRUPT_RECEIVED.store(true, Ordering::SeqCst);
+        }
+    }
 }
 
 pub type StateIpcTx = IpcSender<(Option<usize>, McResult)>;
@@ -70,7 +150,3 @@ pub type StateIpcRx = IpcReceiver<(Option<usize>, McResult)>;
 pub type LemmaIpcTx = IpcSender<(Option<usize>, LitVec)>;
 
 pub type LemmaIpcRx = IpcReceiver<(Option<usize>, LitVec)>;
-
-pub type CertIpcTx = IpcSender<McBlCertificate>;
-
-pub type CertIpcRx = IpcReceiver<McBlCertificate>;
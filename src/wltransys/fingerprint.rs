@@ -0,0 +1,86 @@
+use crate::wltransys::WlTransys;
+use giputils::hash::GHashMap;
+use logicrs::fol::{Term, TermType};
+use std::{
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Deref,
+};
+
+/// Structural hashes of terms, with variables numbered in the order they are
+/// reached, so that equal cones of two elaborations hash equally.
+#[derive(Default)]
+struct ConeHasher {
+    hash: GHashMap<Term, u64>,
+    var_id: GHashMap<Term, usize>,
+    /// latches reached but not expanded yet
+    pending: VecDeque<Term>,
+}
+
+impl ConeHasher {
+    fn term(&mut self, wts: &WlTransys, root: &Term) -> u64 {
+        let mut stack = vec![(root.clone(), false)];
+        while let Some((t, expanded)) = stack.pop() {
+            if self.hash.contains_key(&t) {
+                continue;
+            }
+            let mut h = DefaultHasher::new();
+            format!("{:?}", t.sort()).hash(&mut h);
+            match t.deref() {
+                TermType::Const(c) => format!("c{c:?}").hash(&mut h),
+                TermType::Var(_) => {
+                    let id = self.var_id.len();
+                    self.var_id.insert(t.clone(), id);
+                    let latch = wts.next.contains_key(&t);
+                    if latch {
+                        self.pending.push_back(t.clone());
+                    }
+                    ("v", id, latch).hash(&mut h);
+                }
+                TermType::Op(op) if !expanded => {
+                    stack.push((t.clone(), true));
+                    for s in op.terms.iter().rev() {
+                        if !self.hash.contains_key(s) {
+                            stack.push((s.clone(), false));
+                        }
+                    }
+                    continue;
+                }
+                TermType::Op(op) => {
+                    format!("{:?}", op.op).hash(&mut h);
+                    for s in op.terms.iter() {
+                        self.hash[s].hash(&mut h);
+                    }
+                }
+            }
+            self.hash.insert(t, h.finish());
+        }
+        self.hash[root]
+    }
+}
+
+impl WlTransys {
+    /// A fingerprint of the cone of influence of each bad: its logic, the
+    /// constraints and the reached latches with their init and next. It does
+    /// not depend on signal names, so it tells which properties a design
+    /// change affects.
+    pub fn cone_fingerprints(&self) -> Vec<u64> {
+        self.bad
+            .iter()
+            .map(|bad| {
+                let mut ch = ConeHasher::default();
+                let mut h = DefaultHasher::new();
+                ch.term(self, bad).hash(&mut h);
+                for c in self.constraint.iter() {
+                    ch.term(self, c).hash(&mut h);
+                }
+                while let Some(l) = ch.pending.pop_front() {
+                    ch.var_id[&l].hash(&mut h);
+                    self.init(&l).map(|i| ch.term(self, &i)).hash(&mut h);
+                    ch.term(self, &self.next(&l)).hash(&mut h);
+                }
+                h.finish()
+            })
+            .collect()
+    }
+}
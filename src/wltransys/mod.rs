@@ -1,5 +1,6 @@
 pub mod bitblast;
 pub mod cert;
+mod fingerprint;
 mod simplify;
 pub mod symbol;
 mod test;
@@ -128,3 +128,59 @@ pub fn link_wts_by_symbol(
 
     Ok((linked_wts, linked_wsym, unlinked_symbols))
 }
+
+/// Cuts the signals `names` into free inputs: a cut latch becomes an input,
+/// and other signals are replaced by a fresh input everywhere.
+pub fn cut_wts_by_symbol(
+    wts: &mut WlTransys,
+    wsym: &mut WlTsSymbol,
+    names: &[String],
+) -> anyhow::Result<()> {
+    let mut subst = GHashMap::new();
+    for name in names {
+        let Some(term) = wsym.term_of_sym(name) else {
+            anyhow::bail!("cutpoint signal {name} not found");
+        };
+        let term = term.clone();
+        if wts.input.contains(&term) || subst.contains_key(&term) {
+            continue;
+        }
+        if wts.next.contains_key(&term) {
+            wts.latch.retain(|l| l != &term);
+            wts.init.remove(&term);
+            wts.next.remove(&term);
+            wts.add_input(&term);
+        } else {
+            let input = Term::new_var(term.sort());
+            wts.add_input(&input);
+            subst.insert(term, input);
+        }
+    }
+    if subst.is_empty() {
+        return Ok(());
+    }
+    for v in wts.init.values_mut().chain(wts.next.values_mut()) {
+        *v = apply_subst(v, &subst);
+    }
+    for t in wts
+        .bad
+        .iter_mut()
+        .chain(wts.output.iter_mut())
+        .chain(wts.constraint.iter_mut())
+        .chain(wts.justice.iter_mut())
+    {
+        *t = apply_subst(t, &subst);
+    }
+    let signal: Vec<_> = wsym.signal.iter().map(|(t, _)| t.clone()).collect();
+    for term in signal {
+        let mapped = apply_subst(&term, &subst);
+        if mapped != term
+            && let Some(names) = wsym.remove(&term)
+        {
+            for name in names {
+                wsym.add_symbol(&mapped, name);
+            }
+        }
+    }
+    Ok(())
+}
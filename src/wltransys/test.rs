@@ -36,4 +36,35 @@ mod tests {
         assert_eq!(restored.constraint, vec![Term::bool_const(true)]);
         assert_eq!(restored.justice, vec![bad]);
     }
+
+    /// Two 4-bit accumulators `x` and `y` of an input, with the bads `x == i`
+    /// and `y == i`. `y` adds the input or doubles itself.
+    fn accumulators(y_adds_input: bool) -> WlTransys {
+        let i = Term::new_var(Sort::Bv(4));
+        let x = Term::new_var(Sort::Bv(4));
+        let y = Term::new_var(Sort::Bv(4));
+        let zero = Term::bv_const(BitVec::zero(4));
+        let y_next = if y_adds_input { &y + &i } else { &y + &y };
+        WlTransys {
+            input: vec![i.clone()],
+            latch: vec![x.clone(), y.clone()],
+            init: GHashMap::from_iter([(x.clone(), zero.clone()), (y.clone(), zero)]),
+            next: GHashMap::from_iter([(x.clone(), &x + &i), (y.clone(), y_next)]),
+            bad: vec![x.teq(&i), y.teq(&i)],
+            output: vec![],
+            constraint: vec![Term::bool_const(true)],
+            justice: vec![],
+        }
+    }
+
+    #[test]
+    fn cone_fingerprints_are_stable() {
+        let fp = accumulators(false).cone_fingerprints();
+        assert_eq!(fp.len(), 2);
+        assert_ne!(fp[0], fp[1]);
+        assert_eq!(accumulators(false).cone_fingerprints(), fp);
+        let changed = accumulators(true).cone_fingerprints();
+        assert_eq!(changed[0], fp[0]);
+        assert_ne!(changed[1], fp[1]);
+    }
 }